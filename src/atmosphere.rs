@@ -0,0 +1,118 @@
+use crate::materials::phase::PhaseFunction;
+use crate::prelude::*;
+
+/// A homogeneous medium filling the whole scene, like haze or fog.
+///
+/// The atmosphere fills a ball of radius `extent` around the camera, so light from the
+/// background is dimmed by `exp(-density * extent)` on its way to the camera. Unlike a
+/// `Medium` wrapped in a huge boundary, it costs no extra intersections with the scene:
+/// the ball is intersected analytically and scattering events are sampled along every ray.
+#[derive(Debug, Clone)]
+pub struct Atmosphere {
+    /// The extinction coefficient per unit distance.
+    density: f64,
+    /// The center of the ball filled by the atmosphere.
+    center: Point3,
+    /// The radius of the ball filled by the atmosphere.
+    extent: f64,
+    /// The scattering behaviour at interactions with the atmosphere.
+    phase_function: Arc<Materials>,
+}
+
+impl Atmosphere {
+    /// Creates a new atmosphere.
+    ///
+    /// # Arguments
+    ///
+    /// * `density` - The extinction coefficient per unit distance.
+    /// * `albedo` - The fraction of light scattered, rather than absorbed, at each interaction.
+    /// * `phase` - The angular distribution of scattered light.
+    /// * `extent` - How far the atmosphere reaches from the camera.
+    ///
+    /// # Returns
+    ///
+    /// A new `Atmosphere`.
+    pub fn new(density: f64, albedo: Color3, phase: PhaseFunction, extent: f64) -> Self {
+        Self {
+            density: density.max(0.),
+            center: Point3::zero(),
+            extent: extent.max(0.),
+            phase_function: Materials::volume(Textures::solid_color(albedo), phase),
+        }
+    }
+
+    /// Centers the atmosphere on a point, which the camera sets to its own position.
+    pub(crate) fn centered_at(self, center: Point3) -> Self {
+        Self { center, ..self }
+    }
+
+    /// Returns the part of a ray inside the atmosphere, before `t_max`, as distances in
+    /// scene units from the ray's origin; the part is empty if the start is not before the end.
+    fn inside(&self, ray: &Ray, t_max: f64) -> (f64, f64) {
+        let length = ray.direction.length();
+        let direction = ray.direction / length;
+        let oc = self.center - ray.origin;
+
+        let h = direction.dot(&oc);
+        let discriminant = h * h - (oc.norm() - self.extent * self.extent);
+        if discriminant <= 0. {
+            return (0., 0.);
+        }
+        let root = discriminant.sqrt();
+        ((h - root).max(0.), (h + root).min(t_max * length))
+    }
+
+    /// Samples an interaction with the atmosphere along a ray.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to trace.
+    /// * `t_max` - The ray parameter of the closest surface, or infinity if the ray escapes.
+    /// * `rng` - A random number generator.
+    ///
+    /// # Returns
+    ///
+    /// The hit record of the interaction, or `None` if the ray reaches `t_max` first.
+    pub fn sample(&self, ray: &Ray, t_max: f64, rng: &mut ThreadRng) -> Option<HitRecord> {
+        if self.density <= 0. {
+            return None;
+        }
+        let (start, end) = self.inside(ray, t_max);
+        let hit_distance = start - (1. - gen_01(rng)).ln() / self.density;
+        if hit_distance >= end {
+            return None;
+        }
+
+        let t = hit_distance / ray.direction.length();
+        Some(HitRecord {
+            point: ray.at(t),
+            error: Vec3::zero(),
+            normal: -1. * ray.direction.normalize(),
+            shading_normal: -1. * ray.direction.normalize(),
+            t,
+            u: 0.,
+            v: 0.,
+            dpdu: Vec3::zero(),
+            dpdv: Vec3::zero(),
+            duvdx: (0., 0.),
+            duvdy: (0., 0.),
+            mat: self.phase_function.clone(),
+            front_face: true,
+        })
+    }
+
+    /// Returns the fraction of light that crosses the atmosphere along a ray.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to trace.
+    /// * `t_max` - The ray parameter where the ray ends, or infinity for the background.
+    ///
+    /// # Returns
+    ///
+    /// The transmittance in [0, 1].
+    pub fn transmittance(&self, ray: &Ray, t_max: f64) -> f64 {
+        let (start, end) = self.inside(ray, t_max);
+        (-self.density * (end - start).max(0.)).exp()
+    }
+}
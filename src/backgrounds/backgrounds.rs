@@ -0,0 +1,116 @@
+use crate::backgrounds::environment::EnvironmentMap;
+use crate::backgrounds::sky::Sky;
+use crate::prelude::*;
+
+/// Represents what a ray sees when it escapes the scene without hitting anything.
+///
+/// Backgrounds that carry meaningful illumination can also be importance sampled,
+/// so the camera can use them as a light source.
+#[derive(Debug, Clone)]
+pub enum Background {
+    /// A constant color in every direction.
+    Solid(Color3),
+    /// An equirectangular environment map.
+    Environment(EnvironmentMap),
+    /// An analytic daylight sky with a sun disk.
+    Sky(Sky),
+}
+
+impl Background {
+    /// Creates a constant-color background.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The radiance seen in every direction.
+    ///
+    /// # Returns
+    ///
+    /// A `Background` enum variant containing the solid color.
+    pub fn solid(color: Color3) -> Arc<Self> {
+        Arc::new(Self::Solid(color))
+    }
+
+    /// Creates an environment light from an equirectangular image (`.hdr`, `.exr` or any LDR format).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the image to load.
+    /// * `intensity` - A scale factor applied to the radiance stored in the image.
+    ///
+    /// # Returns
+    ///
+    /// A `Background` enum variant containing the environment map, or an error if the image
+    /// could not be loaded.
+    #[allow(dead_code)]
+    pub fn environment(path: &str, intensity: f64) -> Result<Arc<Self>, Error> {
+        Ok(Arc::new(Self::Environment(EnvironmentMap::new(path, intensity)?)))
+    }
+
+    /// Creates a procedural daylight sky using the Preetham model.
+    ///
+    /// # Arguments
+    ///
+    /// * `sun_direction` - The direction towards the sun.
+    /// * `turbidity` - Atmospheric haziness, from about 2 (very clear) to 10 (hazy).
+    /// * `ground_albedo` - The reflectance of the ground seen below the horizon.
+    /// * `intensity` - Scale factor applied to the model's kcd/m² radiance.
+    ///
+    /// # Returns
+    ///
+    /// A `Background` enum variant containing the sky.
+    pub fn sky(sun_direction: Vec3, turbidity: f64, ground_albedo: Color3, intensity: f64) -> Arc<Self> {
+        Arc::new(Self::Sky(Sky::new(sun_direction, turbidity, ground_albedo, intensity)))
+    }
+
+    /// Returns the radiance arriving along an escaped ray.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray that left the scene.
+    ///
+    /// # Returns
+    ///
+    /// The radiance as a `Color3`.
+    pub fn value(&self, ray: &Ray) -> Color3 {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Environment(env) => env.value(&ray.direction),
+            Self::Sky(sky) => sky.value(&ray.direction),
+        }
+    }
+
+    /// Samples a direction towards the background, proportionally to its brightness.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - A random number generator.
+    ///
+    /// # Returns
+    ///
+    /// The sampled unit direction and its solid-angle pdf, or `None` if this background
+    /// is not used as a light source.
+    pub fn sample(&self, rng: &mut ThreadRng) -> Option<(Vec3, f64)> {
+        match self {
+            Self::Solid(_) => None,
+            Self::Environment(env) => env.sample(rng),
+            Self::Sky(sky) => sky.sample(rng),
+        }
+    }
+
+    /// Returns the solid-angle pdf with which `sample` would pick the given direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The pdf, or `0.0` for backgrounds that are never sampled.
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        match self {
+            Self::Solid(_) => 0.,
+            Self::Environment(env) => env.pdf(direction),
+            Self::Sky(sky) => sky.pdf(direction),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::distribution::Distribution2D;
+use crate::prelude::*;
+use image::{ColorType, ImageReader};
+
+/// An infinitely distant light described by an equirectangular (latitude-longitude) image.
+///
+/// Row 0 of the image is straight up (`+y`), and the horizontal mapping matches the
+/// texture coordinates produced by `Sphere`, so the same image lines up on both.
+///
+/// # Fields
+/// - `width`, `height`: The image dimensions in pixels.
+/// - `pixels`: The linear radiance of each pixel, row-major.
+/// - `intensity`: A scale factor applied to every pixel.
+/// - `distribution`: The luminance-based distribution used for importance sampling.
+#[derive(Debug, Clone)]
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color3>,
+    intensity: f64,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    /// Loads an environment map from disk.
+    ///
+    /// Floating-point images (`.hdr`, `.exr`) are assumed to hold linear radiance; 8 and 16 bit
+    /// images are assumed to be sRGB encoded and are linearized.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the image to load.
+    /// - `intensity`: A scale factor applied to the radiance stored in the image.
+    ///
+    /// # Returns
+    /// A new `EnvironmentMap`, or an error if the image could not be opened or decoded.
+    pub fn new(path: &str, intensity: f64) -> Result<Self, Error> {
+        let image = ImageReader::open(path)?.decode().map_err(Error::other)?;
+        let linear = matches!(image.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+        let image = image.to_rgb32f();
+
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+
+        let decode = |c: f32| {
+            let c = c as f64;
+            if linear {
+                c
+            } else if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let pixels: Vec<Color3> = image
+            .pixels()
+            .map(|p| Color3::new(decode(p[0]), decode(p[1]), decode(p[2])))
+            .collect();
+
+        // Weight each pixel by its luminance and by the solid angle it covers, which shrinks
+        // towards the poles.
+        let mut weights = Vec::with_capacity(width * height);
+        for j in 0..height {
+            let sin_theta = (PI * (j as f64 + 0.5) / height as f64).sin();
+            for i in 0..width {
+                weights.push(pixels[j * width + i].luminance() * sin_theta);
+            }
+        }
+        let distribution = Distribution2D::new(&weights, width, height);
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            intensity,
+            distribution,
+        })
+    }
+
+    /// Maps a direction to equirectangular image coordinates in [0, 1]².
+    fn direction_to_uv(direction: &Vec3) -> (f64, f64) {
+        let d = direction.normalize();
+        let theta = d.y.clamp(-1., 1.).acos();
+        let phi = (-d.z).atan2(d.x) + PI;
+
+        (phi / (2. * PI), theta / PI)
+    }
+
+    /// Maps equirectangular image coordinates back to a unit direction.
+    fn uv_to_direction(u: f64, v: f64) -> Vec3 {
+        let phi = 2. * PI * u;
+        let theta = PI * v;
+        let sin_theta = theta.sin();
+
+        Vec3::new(-phi.cos() * sin_theta, theta.cos(), phi.sin() * sin_theta)
+    }
+
+    /// Returns the radiance arriving from the given direction.
+    pub fn value(&self, direction: &Vec3) -> Color3 {
+        let (u, v) = Self::direction_to_uv(direction);
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+
+        self.intensity * self.pixels[j * self.width + i]
+    }
+
+    /// Samples a direction proportionally to the luminance of the map.
+    ///
+    /// # Returns
+    /// The sampled unit direction and its solid-angle pdf, or `None` if the sample is degenerate.
+    pub fn sample(&self, rng: &mut ThreadRng) -> Option<(Vec3, f64)> {
+        let ((u, v), map_pdf) = self.distribution.sample(gen_01(rng), gen_01(rng));
+        if map_pdf == 0. {
+            return None;
+        }
+
+        let sin_theta = (PI * v).sin();
+        if sin_theta == 0. {
+            return None;
+        }
+
+        // Convert from a density over the image to a density over solid angle.
+        let pdf = map_pdf / (2. * PI * PI * sin_theta);
+        Some((Self::uv_to_direction(u, v), pdf))
+    }
+
+    /// Returns the solid-angle pdf with which `sample` picks the given direction.
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        let (u, v) = Self::direction_to_uv(direction);
+        let sin_theta = (PI * v).sin();
+        if sin_theta == 0. {
+            return 0.;
+        }
+
+        self.distribution.pdf(u, v) / (2. * PI * PI * sin_theta)
+    }
+}
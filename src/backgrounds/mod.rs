@@ -0,0 +1,5 @@
+mod environment;
+mod sky;
+
+#[allow(clippy::module_inception)]
+pub mod backgrounds;
@@ -0,0 +1,245 @@
+use crate::prelude::*;
+
+/// Angular radius of the sun as seen from the earth, in degrees.
+const SUN_ANGULAR_RADIUS: f64 = 0.267;
+/// Luminance of the sun above the atmosphere, in kcd/m² to match the sky model.
+const SUN_LUMINANCE: f64 = 1.96e6;
+/// Wavelengths (in micrometers) used to attenuate sunlight for the red, green and blue channels.
+const SUN_WAVELENGTHS: [f64; 3] = [0.680, 0.550, 0.440];
+
+/// The five coefficients of the Perez sky luminance distribution.
+#[derive(Debug, Clone, Copy)]
+struct Perez {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+}
+
+impl Perez {
+    /// Evaluates the Perez function for a view zenith angle `theta` and sun angle `gamma`.
+    fn eval(&self, cos_theta: f64, gamma: f64) -> f64 {
+        let cos_gamma = gamma.cos();
+        (1. + self.a * (self.b / cos_theta.max(0.01)).exp())
+            * (1. + self.c * (self.d * gamma).exp() + self.e * cos_gamma * cos_gamma)
+    }
+}
+
+/// The analytic daylight sky of Preetham, Shirley and Smits (1999), with a sun disk.
+///
+/// Sky radiance is computed in the model's native kcd/m² and then multiplied by `intensity`,
+/// so an intensity around `0.15` gives values comparable to the constant backgrounds used
+/// elsewhere. The sun is attenuated by Rayleigh and aerosol extinction and can be importance
+/// sampled, so it acts as a light source.
+///
+/// # Fields
+/// - `sun_direction`: Unit vector pointing towards the sun.
+/// - `sun_radiance`: Radiance of the sun disk after atmospheric extinction.
+/// - `cos_sun_radius`: Cosine of the sun's angular radius.
+/// - `sun_frame`: Basis around the sun direction used for sampling the disk.
+/// - `perez_y`, `perez_x`, `perez_yc`: Perez coefficients for luminance and the two chromaticities.
+/// - `zenith`: Luminance and chromaticity at the zenith, as `(Y, x, y)`.
+/// - `ground`: Radiance of the ground below the horizon.
+/// - `intensity`: Scale factor applied to all returned radiance.
+#[derive(Debug, Clone)]
+pub struct Sky {
+    sun_direction: Vec3,
+    sun_radiance: Color3,
+    cos_sun_radius: f64,
+    sun_frame: Onb,
+    perez_y: Perez,
+    perez_x: Perez,
+    perez_yc: Perez,
+    zenith: Vec3,
+    ground: Color3,
+    intensity: f64,
+}
+
+impl Sky {
+    /// Creates a new daylight sky.
+    ///
+    /// # Arguments
+    /// - `sun_direction`: The direction towards the sun. Does not need to be normalized.
+    /// - `turbidity`: Atmospheric haziness, from about 2 (very clear) to 10 (hazy).
+    /// - `ground_albedo`: The reflectance of the ground plane seen below the horizon.
+    /// - `intensity`: Scale factor applied to the model's kcd/m² radiance.
+    ///
+    /// # Returns
+    /// A new `Sky` instance.
+    pub fn new(sun_direction: Vec3, turbidity: f64, ground_albedo: Color3, intensity: f64) -> Self {
+        let sun_direction = sun_direction.normalize();
+        let t = turbidity;
+
+        // The model is only defined for the sun above the horizon.
+        let theta_s = sun_direction.y.clamp(0., 1.).acos();
+        let theta_s2 = theta_s * theta_s;
+        let theta_s3 = theta_s2 * theta_s;
+
+        let chi = (4. / 9. - t / 120.) * (PI - 2. * theta_s);
+        let zenith_y = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = t * t * (0.00166 * theta_s3 - 0.00375 * theta_s2 + 0.00209 * theta_s)
+            + t * (-0.02903 * theta_s3 + 0.06377 * theta_s2 - 0.03202 * theta_s + 0.00394)
+            + (0.11693 * theta_s3 - 0.21196 * theta_s2 + 0.06052 * theta_s + 0.25886);
+        let zenith_yc = t * t * (0.00275 * theta_s3 - 0.00610 * theta_s2 + 0.00317 * theta_s)
+            + t * (-0.04214 * theta_s3 + 0.08970 * theta_s2 - 0.04153 * theta_s + 0.00516)
+            + (0.15346 * theta_s3 - 0.26756 * theta_s2 + 0.06670 * theta_s + 0.26688);
+
+        let perez_y = Perez {
+            a: 0.1787 * t - 1.4630,
+            b: -0.3554 * t + 0.4275,
+            c: -0.0227 * t + 5.3251,
+            d: 0.1206 * t - 2.5771,
+            e: -0.0670 * t + 0.3703,
+        };
+        let perez_x = Perez {
+            a: -0.0193 * t - 0.2592,
+            b: -0.0665 * t + 0.0008,
+            c: -0.0004 * t + 0.2125,
+            d: -0.0641 * t - 0.8989,
+            e: -0.0033 * t + 0.0452,
+        };
+        let perez_yc = Perez {
+            a: -0.0167 * t - 0.2608,
+            b: -0.0950 * t + 0.0092,
+            c: -0.0079 * t + 0.2102,
+            d: -0.0441 * t - 1.6537,
+            e: -0.0109 * t + 0.0529,
+        };
+
+        let sun_radiance = if sun_direction.y > 0. {
+            Self::sun_transmittance(theta_s, t) * SUN_LUMINANCE
+        } else {
+            Color3::zero()
+        };
+
+        let mut sky = Self {
+            sun_direction,
+            sun_radiance,
+            cos_sun_radius: degrees_to_radians(SUN_ANGULAR_RADIUS).cos(),
+            sun_frame: Onb::new(&sun_direction),
+            perez_y,
+            perez_x,
+            perez_yc,
+            zenith: Vec3::new(zenith_y, zenith_x, zenith_yc),
+            ground: Color3::zero(),
+            intensity,
+        };
+        sky.ground = ground_albedo * (sky.irradiance() / PI);
+        sky
+    }
+
+    /// Computes the fraction of sunlight that reaches the ground for each color channel.
+    fn sun_transmittance(theta_s: f64, turbidity: f64) -> Color3 {
+        // Relative optical air mass (Kasten and Young).
+        let theta_deg = theta_s * 180. / PI;
+        let air_mass = 1. / (theta_s.cos() + 0.15 * (93.885 - theta_deg).powf(-1.253));
+
+        // Angstrom's turbidity formula for aerosols.
+        let beta = 0.04608 * turbidity - 0.04586;
+        let alpha = 1.3;
+
+        let channel = |lambda: f64| {
+            let rayleigh = 0.008735 * lambda.powf(-4.08);
+            let aerosol = beta * lambda.powf(-alpha);
+            (-(rayleigh + aerosol) * air_mass).exp()
+        };
+
+        Color3::new(
+            channel(SUN_WAVELENGTHS[0]),
+            channel(SUN_WAVELENGTHS[1]),
+            channel(SUN_WAVELENGTHS[2]),
+        )
+    }
+
+    /// Integrates the sky and sun over the upper hemisphere to get the irradiance on the ground.
+    fn irradiance(&self) -> Color3 {
+        let n_theta = 32;
+        let n_phi = 64;
+        let d_theta = 0.5 * PI / n_theta as f64;
+        let d_phi = 2. * PI / n_phi as f64;
+
+        let mut sum = Color3::zero();
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+                sum = sum + self.sky_radiance(&direction) * (theta.cos() * theta.sin() * d_theta * d_phi);
+            }
+        }
+
+        let sun_solid_angle = 2. * PI * (1. - self.cos_sun_radius);
+        sum + self.sun_radiance * (sun_solid_angle * self.sun_direction.y.max(0.))
+    }
+
+    /// Evaluates the Preetham sky (without the sun disk) for a unit direction above the horizon.
+    fn sky_radiance(&self, direction: &Vec3) -> Color3 {
+        let cos_theta = direction.y;
+        let theta_s = self.sun_direction.y.clamp(0., 1.).acos();
+        let gamma = direction.dot(&self.sun_direction).clamp(-1., 1.).acos();
+
+        let relative = |perez: &Perez| perez.eval(cos_theta, gamma) / perez.eval(1., theta_s);
+        let luminance = self.zenith.x * relative(&self.perez_y);
+        let x = self.zenith.y * relative(&self.perez_x);
+        let y = self.zenith.z * relative(&self.perez_yc);
+
+        // xyY to XYZ to linear sRGB.
+        let big_x = x / y * luminance;
+        let big_z = (1. - x - y) / y * luminance;
+        Color3::new(
+            (3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z).max(0.),
+            (-0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z).max(0.),
+            (0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z).max(0.),
+        )
+    }
+
+    /// Returns whether a unit direction falls inside the sun disk.
+    fn in_sun(&self, direction: &Vec3) -> bool {
+        self.sun_direction.y > 0. && direction.dot(&self.sun_direction) >= self.cos_sun_radius
+    }
+
+    /// Returns the radiance arriving from the given direction.
+    pub fn value(&self, direction: &Vec3) -> Color3 {
+        let direction = direction.normalize();
+        if direction.y <= 0. {
+            return self.ground * self.intensity;
+        }
+
+        let mut radiance = self.sky_radiance(&direction);
+        if self.in_sun(&direction) {
+            radiance = radiance + self.sun_radiance;
+        }
+        radiance * self.intensity
+    }
+
+    /// Samples a direction uniformly inside the sun disk.
+    ///
+    /// # Returns
+    /// The sampled unit direction and its solid-angle pdf, or `None` when the sun has set.
+    pub fn sample(&self, rng: &mut ThreadRng) -> Option<(Vec3, f64)> {
+        if self.sun_direction.y <= 0. {
+            return None;
+        }
+
+        let cos_theta = 1. - gen_01(rng) * (1. - self.cos_sun_radius);
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * gen_01(rng);
+        let local = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta);
+
+        Some((self.sun_frame.transform(&local), self.sun_pdf()))
+    }
+
+    /// Returns the solid-angle pdf with which `sample` picks the given direction.
+    pub fn pdf(&self, direction: &Vec3) -> f64 {
+        if self.in_sun(&direction.normalize()) {
+            self.sun_pdf()
+        } else {
+            0.
+        }
+    }
+
+    fn sun_pdf(&self) -> f64 {
+        1. / (2. * PI * (1. - self.cos_sun_radius))
+    }
+}
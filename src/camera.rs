@@ -1,4 +1,8 @@
+use crate::atmosphere::Atmosphere;
 use crate::prelude::*;
+use crate::ray::RayDifferentials;
+use crate::spectrum;
+use crate::tone_map::ToneMap;
 use std::thread;
 use indicatif::{ProgressBar, ProgressStyle};
 
@@ -25,9 +29,19 @@ pub struct CamArgs {
     /// The focus distance for depth of field effects.
     pub focus_dist: f64,
 
-    pub background : Color3,
+    /// What rays see when they leave the scene; environment backgrounds also act as lights.
+    pub background : Arc<Background>,
     // The number of threads to use for rendering.
     pub thread_num: usize,
+    /// The exposure adjustment in stops, applied before tone mapping.
+    pub exposure: f64,
+    /// The tone-mapping operator used when writing the image.
+    pub tone_map: ToneMap,
+    /// Whether to trace a single sampled wavelength per path instead of RGB, which
+    /// lets dispersive materials split light into its spectrum.
+    pub spectral: bool,
+    /// A homogeneous fog filling the whole scene, or `None` for clear air.
+    pub atmosphere: Option<Atmosphere>,
 }
 
 /// Represents a camera in the ray tracer.
@@ -58,9 +72,18 @@ pub struct Camera {
     /// The vertical basis vector for the defocus disk.
     defocus_disk_v: Vec3,
 
-    pub background_color : Color3,
+    /// What rays see when they leave the scene.
+    pub background : Arc<Background>,
     // The number of threads to use for rendering.
     thread_num: usize,
+    /// The linear scale factor derived from the exposure in stops.
+    exposure_scale: f64,
+    /// The tone-mapping operator used when writing the image.
+    tone_map: ToneMap,
+    /// Whether paths carry a sampled wavelength instead of RGB.
+    spectral: bool,
+    /// The fog filling the scene, if any.
+    atmosphere: Option<Atmosphere>,
 }
 
 impl Camera {
@@ -84,8 +107,12 @@ impl Camera {
         let v_up = args.v_up;
         let defocus_angle = args.defocus_angle;
         let focus_dist = args.focus_dist;
-        let background_color = args.background;
+        let background = args.background;
         let thread_num = args.thread_num;
+        let exposure_scale = f64::powf(2.0, args.exposure);
+        let tone_map = args.tone_map;
+        let spectral = args.spectral;
+        let atmosphere = args.atmosphere.map(|atmosphere| atmosphere.centered_at(look_from));
 
         let image_height = (image_width as f64 / aspect_ratio) as usize;
         let image_height = if image_height < 1 { 1 } else { image_height };
@@ -134,39 +161,172 @@ impl Camera {
             defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
-            background_color,
+            background,
             thread_num,
+            exposure_scale,
+            tone_map,
+            spectral,
+            atmosphere,
         }
     }
 
     /// Computes the color for a given ray by tracing it through the scene.
     ///
+    /// Light from the background is gathered both by sampling it directly at every
+    /// hit and by rays that escape the scene, and the two estimates are
+    /// combined with multiple importance sampling.
+    ///
+    /// In spectral mode every color is projected onto the ray's wavelength, so the returned
+    /// radiance holds the same value in all three channels.
+    ///
     /// # Arguments
     ///
     /// * `ray` - The ray to trace.
     /// * `world` - The scene represented as a BVH node.
     /// * `depth` - The remaining recursion depth.
+    /// * `scatter_pdf` - The pdf the material sampled `ray` with, or `None` for camera rays
+    ///   and specular bounces.
     /// * `rng` - A random number generator.
     ///
     /// # Returns
     ///
     /// The computed color as a `Color3`.
-    fn ray_color(&self, ray: &Ray, world: &Hittables, depth: i32, rng: &mut ThreadRng) -> Color3 {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &Hittables,
+        depth: i32,
+        scatter_pdf: Option<f64>,
+        rng: &mut ThreadRng,
+    ) -> Color3 {
         if depth <= 0 {
             return Color3::zero();
         }
 
-        if let Some(hr) = world.hit(ray, Interval::new(0.001, INF), rng) {
-            let color_from_emission = hr.mat.emitted(hr.u, hr.v, &hr.point);
-
-            if let Some((attenuation, scattered)) = hr.mat.scatter(ray, &hr, rng) {
-                let color_from_scatter =  attenuation * self.ray_color(&scattered, world, depth - 1, rng);
-                color_from_emission + color_from_scatter
+        // Rays leaving a surface start past its error bounds, so nothing closer needs skipping.
+        let surface = world.hit(ray, Interval::new(0., INF), rng);
+        let fog = self.atmosphere.as_ref().and_then(|atmosphere| {
+            atmosphere.sample(ray, surface.as_ref().map_or(INF, |hr| hr.t), rng)
+        });
+
+        if let Some(mut hr) = fog.or(surface) {
+            hr.set_differentials(ray);
+            let color_from_emission =
+                spectrum::at_wavelength(hr.mat.emitted(&hr), ray.wavelength);
+
+            // Specular lobes evaluate to zero, so this only costs a shadow ray for materials
+            // with a non-specular part, even when the lobe sampled below is specular.
+            let color_from_light = self.sample_background(ray, &hr, world, rng);
+
+            if let Some(mut srec) = hr.mat.scatter(ray, &hr, rng) {
+                let correction = Self::shading_correction(&hr, &srec.scattered.direction);
+                if correction <= 0. {
+                    return color_from_emission + color_from_light;
+                }
+                srec.scattered.wavelength = ray.wavelength;
+                let color_from_scatter = spectrum::at_wavelength(srec.attenuation * correction, ray.wavelength)
+                    * self.ray_color(&srec.scattered, world, depth - 1, srec.pdf, rng);
+                color_from_emission + color_from_light + color_from_scatter
             } else {
-                color_from_emission
+                color_from_emission + color_from_light
             }
         } else {
-            self.background_color
+            let weight = match scatter_pdf {
+                Some(pdf) => Self::power_heuristic(pdf, self.background.pdf(&ray.direction)),
+                None => 1.,
+            };
+            weight * spectrum::at_wavelength(self.background.value(ray), ray.wavelength)
+        }
+    }
+
+    /// Estimates the light arriving at a hit point directly from the background by sampling it.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray that produced the hit.
+    /// * `hr` - The hit record of the surface being shaded.
+    /// * `world` - The scene, used to test visibility.
+    /// * `rng` - A random number generator.
+    ///
+    /// # Returns
+    ///
+    /// The MIS-weighted direct lighting contribution as a `Color3`.
+    fn sample_background(&self, ray: &Ray, hr: &HitRecord, world: &Hittables, rng: &mut ThreadRng) -> Color3 {
+        let Some((direction, light_pdf)) = self.background.sample(rng) else {
+            return Color3::zero();
+        };
+
+        let f = hr.mat.eval(ray, hr, &direction) * Self::shading_correction(hr, &direction);
+        if f.near_zero() {
+            return Color3::zero();
+        }
+
+        let mut shadow_ray = hr.spawn_ray(direction, ray.time);
+        shadow_ray.wavelength = ray.wavelength;
+        let mut transmittance = world.transmittance(&shadow_ray, Interval::new(0., INF), rng);
+        if let Some(atmosphere) = &self.atmosphere {
+            transmittance *= atmosphere.transmittance(&shadow_ray, INF);
+        }
+        if transmittance <= 0. {
+            return Color3::zero();
+        }
+
+        let weight = Self::power_heuristic(light_pdf, hr.mat.pdf(ray, hr, &direction));
+        spectrum::at_wavelength(f, ray.wavelength)
+            * spectrum::at_wavelength(self.background.value(&shadow_ray), ray.wavelength)
+            * (transmittance * weight / light_pdf)
+    }
+
+    /// Corrects a material sample or evaluation for the shading normal differing from the geometric one.
+    ///
+    /// Directions the two normals put on opposite sides of the surface are dropped, since they
+    /// would let light leak through it. Light grazing the geometric surface is smoothly shadowed
+    /// (Chiang et al. 2019), which softens the hard shadow terminator that bent normals leave on
+    /// coarse geometry.
+    ///
+    /// # Arguments
+    ///
+    /// * `hr` - The hit record of the surface being shaded.
+    /// * `direction` - The direction light arrives from or is scattered to.
+    ///
+    /// # Returns
+    ///
+    /// The factor in [0, 1] to multiply the material's contribution by.
+    fn shading_correction(hr: &HitRecord, direction: &Vec3) -> f64 {
+        if (hr.shading_normal - hr.normal).norm() < 1e-16 {
+            return 1.;
+        }
+
+        let geometric = direction.dot(&hr.normal);
+        let shading = direction.dot(&hr.shading_normal);
+        if geometric * shading <= 0. {
+            return 0.;
+        }
+        if geometric < 0. {
+            return 1.;
+        }
+
+        let g = (geometric / (shading * hr.shading_normal.dot(&hr.normal))).min(1.);
+        -g * g * g + g * g + g
+    }
+
+    /// The power heuristic (with exponent 2) for combining two sampling strategies.
+    ///
+    /// # Arguments
+    ///
+    /// * `pdf` - The pdf of the strategy whose sample is being weighted.
+    /// * `other_pdf` - The pdf the other strategy would have produced the same sample with.
+    ///
+    /// # Returns
+    ///
+    /// The MIS weight of the sample.
+    fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+        let a = pdf * pdf;
+        let b = other_pdf * other_pdf;
+        if a + b == 0. {
+            0.
+        } else {
+            a / (a + b)
         }
     }
 
@@ -223,7 +383,17 @@ impl Camera {
 
         let ray_time = gen_01(rng);
 
-        Ray::new_time(ray_origin, ray_direction, ray_time)
+        // The rays through the same point of the neighbouring pixels, with the spacing shrunk
+        // as the samples of a pixel get denser.
+        let mut ray = Ray::new_time(ray_origin, ray_direction, ray_time);
+        ray.differentials = Some(RayDifferentials {
+            rx_origin: ray_origin,
+            rx_direction: ray_direction + self.pixel_delta_u,
+            ry_origin: ray_origin,
+            ry_direction: ray_direction + self.pixel_delta_v,
+        });
+        ray.scale_differentials(1. / (self.samples_per_pixel as f64).sqrt());
+        ray
     }
 
     /// Renders a single scan line of the image.
@@ -241,9 +411,20 @@ impl Camera {
         let mut scan_line = Vec::new();
         for i in 0..self.image_width {
             let mut pixel_color = Color3::new(0.0, 0.0, 0.0);
-            for _ in 0..self.samples_per_pixel {
-                let r = self.get_ray(i, j, rng);
-                pixel_color = pixel_color + self.ray_color(&r, world, self.max_depth, rng);
+            for sample in 0..self.samples_per_pixel {
+                let mut r = self.get_ray(i, j, rng);
+                if self.spectral {
+                    let wavelength = spectrum::sample_wavelength(
+                        sample as usize,
+                        self.samples_per_pixel as usize,
+                        rng,
+                    );
+                    r.wavelength = Some(wavelength);
+                    let radiance = self.ray_color(&r, world, self.max_depth, None, rng);
+                    pixel_color = pixel_color + spectrum::spectrum_to_rgb(radiance.x, wavelength);
+                } else {
+                    pixel_color = pixel_color + self.ray_color(&r, world, self.max_depth, None, rng);
+                }
             }
             scan_line.push(pixel_color * self.pixel_samples_scale);
         }
@@ -331,6 +512,7 @@ impl Camera {
         lines
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn calculate_img(&self, world: Hittables) -> Vec<Vec<Color3>> {
         let progress_bar = ProgressBar::new(self.image_height as u64);
         progress_bar.set_style(
@@ -367,7 +549,8 @@ impl Camera {
 
         for line in img.iter() {
             for pixel in line.iter() {
-                pixel.writeln_color(&mut file)?;
+                let mapped = self.tone_map.apply(*pixel * self.exposure_scale);
+                mapped.writeln_color(&mut file)?;
             }
         }
         println!("Done!");
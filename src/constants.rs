@@ -2,9 +2,17 @@
 pub const INF: f64 = f64::INFINITY;
 pub const NEG_INF: f64 = f64::NEG_INFINITY;
 pub const PI: f64 = std::f64::consts::PI;
+/// Half the gap between 1 and the next float, i.e. the relative error of one rounded operation.
+pub const MACHINE_EPSILON: f64 = f64::EPSILON * 0.5;
 
 // Utility Functions
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
+
+/// A bound on the relative error accumulated by `n` consecutive rounded floating-point operations.
+pub fn gamma(n: u32) -> f64 {
+    let e = n as f64 * MACHINE_EPSILON;
+    e / (1.0 - e)
+}
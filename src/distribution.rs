@@ -0,0 +1,197 @@
+/// A piecewise-constant 1D distribution over [0, 1), used for importance sampling tabulated functions.
+///
+/// # Fields
+/// - `func`: The (non-negative) function values of each bucket.
+/// - `cdf`: The normalized cumulative distribution, with `func.len() + 1` entries.
+/// - `integral`: The integral of the function over [0, 1).
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    func: Vec<f64>,
+    cdf: Vec<f64>,
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Builds a distribution proportional to the given bucket values.
+    ///
+    /// # Arguments
+    /// - `func`: The function value of each bucket. Negative values are treated as zero.
+    ///
+    /// # Returns
+    /// A new `Distribution1D`. If every value is zero the distribution falls back to uniform.
+    pub fn new(func: &[f64]) -> Self {
+        let n = func.len();
+        let func: Vec<f64> = func.iter().map(|f| f.max(0.)).collect();
+
+        let mut cdf = vec![0.; n + 1];
+        for i in 1..=n {
+            cdf[i] = cdf[i - 1] + func[i - 1] / n as f64;
+        }
+
+        let integral = cdf[n];
+        if integral == 0. {
+            for (i, c) in cdf.iter_mut().enumerate() {
+                *c = i as f64 / n as f64;
+            }
+        } else {
+            for c in cdf.iter_mut() {
+                *c /= integral;
+            }
+        }
+
+        Self { func, cdf, integral }
+    }
+
+    /// Returns the number of buckets in the distribution.
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    /// Returns the integral of the function over [0, 1).
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Samples a continuous value in [0, 1) proportionally to the function.
+    ///
+    /// # Arguments
+    /// - `u`: A uniform random number in [0, 1).
+    ///
+    /// # Returns
+    /// A tuple of the sampled value, its probability density, and the index of the bucket it falls in.
+    pub fn sample_continuous(&self, u: f64) -> (f64, f64, usize) {
+        // Find the last cdf entry that is <= u.
+        let offset = self.cdf.partition_point(|&c| c <= u).clamp(1, self.count()) - 1;
+
+        let mut du = u - self.cdf[offset];
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        if width > 0. {
+            du /= width;
+        }
+
+        let pdf = self.pdf(offset);
+        let x = (offset as f64 + du) / self.count() as f64;
+        (x, pdf, offset)
+    }
+
+    /// Returns the probability density of the given bucket.
+    pub fn pdf(&self, index: usize) -> f64 {
+        if self.integral > 0. {
+            self.func[index] / self.integral
+        } else {
+            1.
+        }
+    }
+}
+
+/// A piecewise-constant 2D distribution over [0, 1)², built from a row-major grid of values.
+///
+/// # Fields
+/// - `conditional`: One distribution over `u` for each row.
+/// - `marginal`: The distribution over rows (`v`).
+#[derive(Debug, Clone)]
+pub struct Distribution2D {
+    conditional: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Builds a distribution proportional to a `nu` x `nv` grid of values.
+    ///
+    /// # Arguments
+    /// - `func`: The grid values in row-major order (`nu` values per row).
+    /// - `nu`: The number of columns.
+    /// - `nv`: The number of rows.
+    ///
+    /// # Returns
+    /// A new `Distribution2D`.
+    pub fn new(func: &[f64], nu: usize, nv: usize) -> Self {
+        let conditional: Vec<Distribution1D> = func
+            .chunks(nu)
+            .take(nv)
+            .map(Distribution1D::new)
+            .collect();
+        let marginal_func: Vec<f64> = conditional.iter().map(|d| d.integral()).collect();
+        let marginal = Distribution1D::new(&marginal_func);
+
+        Self { conditional, marginal }
+    }
+
+    /// Samples a point in [0, 1)² proportionally to the grid values.
+    ///
+    /// # Arguments
+    /// - `u0`: A uniform random number used to pick the column.
+    /// - `u1`: A uniform random number used to pick the row.
+    ///
+    /// # Returns
+    /// A tuple of the sampled `(u, v)` coordinates and their joint probability density.
+    pub fn sample(&self, u0: f64, u1: f64) -> ((f64, f64), f64) {
+        let (v, pdf_v, row) = self.marginal.sample_continuous(u1);
+        let (u, pdf_u, _) = self.conditional[row].sample_continuous(u0);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    /// Returns the joint probability density of the point `(u, v)`.
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let row_dist = &self.conditional[0];
+        let col = ((u * row_dist.count() as f64) as usize).min(row_dist.count() - 1);
+        let row = ((v * self.marginal.count() as f64) as usize).min(self.marginal.count() - 1);
+
+        if self.marginal.integral() == 0. {
+            return 1.;
+        }
+        self.conditional[row].func[col] / self.marginal.integral()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pdf_1d_integrates_to_one() {
+        let dist = Distribution1D::new(&[0., 1., 3., 0.5, 0., 2.]);
+        let total: f64 = (0..dist.count()).map(|i| dist.pdf(i) / dist.count() as f64).sum();
+        assert!((total - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sample_1d_matches_pdf_and_skips_empty_buckets() {
+        let dist = Distribution1D::new(&[0., 1., 3., 0.]);
+        for i in 0..100 {
+            let (x, pdf, index) = dist.sample_continuous(i as f64 / 100.);
+            assert!(index == 1 || index == 2);
+            assert_eq!(pdf, dist.pdf(index));
+            assert!((index as f64 / 4. ..=(index + 1) as f64 / 4.).contains(&x));
+        }
+    }
+
+    #[test]
+    fn zero_function_falls_back_to_uniform() {
+        let dist = Distribution1D::new(&[0., 0., 0.]);
+        let (x, pdf, _) = dist.sample_continuous(0.5);
+        assert!((x - 0.5).abs() < 1e-12);
+        assert_eq!(pdf, 1.);
+    }
+
+    #[test]
+    fn pdf_2d_integrates_to_one_and_matches_samples() {
+        let (nu, nv) = (5, 3);
+        let func: Vec<f64> = (0..nu * nv).map(|i| (i % 4) as f64).collect();
+        let dist = Distribution2D::new(&func, nu, nv);
+
+        let mut total = 0.;
+        for row in 0..nv {
+            for col in 0..nu {
+                let (u, v) = ((col as f64 + 0.5) / nu as f64, (row as f64 + 0.5) / nv as f64);
+                total += dist.pdf(u, v) / (nu * nv) as f64;
+            }
+        }
+        assert!((total - 1.).abs() < 1e-12);
+
+        for i in 0..50 {
+            let ((u, v), pdf) = dist.sample(i as f64 / 50., (i * 7 % 50) as f64 / 50.);
+            assert!((pdf - dist.pdf(u, v)).abs() < 1e-9);
+        }
+    }
+}
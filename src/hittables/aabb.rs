@@ -6,6 +6,7 @@ use crate::prelude::*;
 /// - `x`: The interval along the x-axis.
 /// - `y`: The interval along the y-axis.
 /// - `z`: The interval along the z-axis.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug, Copy)]
 pub struct AABB {
     x: Interval,
@@ -48,13 +49,17 @@ impl AABB {
         }
     }
 
-    // pub fn universe() -> Self {
-    //     Self {
-    //         x : Interval::universe(),
-    //         y : Interval::universe(),
-    //         z : Interval::universe(),
-    //     }
-    // }
+    /// Returns an AABB covering all of space, for unbounded objects such as planes.
+    ///
+    /// # Returns
+    /// An `AABB` with infinite intervals on all axes.
+    pub fn universe() -> Self {
+        Self {
+            x : Interval::universe(),
+            y : Interval::universe(),
+            z : Interval::universe(),
+        }
+    }
 
     /// Creates an AABB that encloses two points.
     ///
@@ -98,6 +103,23 @@ impl AABB {
         Self { x, y, z }
     }
 
+    /// Returns the AABB enclosing the space inside both AABBs.
+    ///
+    /// # Arguments
+    /// - `a`: The first AABB.
+    /// - `b`: The second AABB.
+    ///
+    /// # Returns
+    /// An `AABB` that encloses the overlap of the input AABBs, which is empty if they do not overlap.
+    pub fn overlap(a: &AABB, b: &AABB) -> Self {
+        let axis = |x: Interval, y: Interval| Interval::new(x.min.max(y.min), x.max.min(y.max));
+        Self {
+            x: axis(a.x, b.x),
+            y: axis(a.y, b.y),
+            z: axis(a.z, b.z),
+        }
+    }
+
     /// Determines the longest axis of the AABB.
     ///
     /// # Returns
@@ -38,8 +38,8 @@ impl BVHNode {
         }
 
         let mut bbox = AABB::empty();
-        for obj in objects.iter() {
-            bbox = AABB::from_boxes(&bbox, &obj.bounding_box());
+        for obj in objects[start..end].iter() {
+            bbox = AABB::from_boxes(&bbox, obj.bounding_box());
         }
 
         let axis = bbox.longest_axis();
@@ -86,11 +86,9 @@ impl BVHNode {
         let a_axis_interval = a.bounding_box().axis_interval(axis);
         let b_axis_interval = b.bounding_box().axis_interval(axis);
 
-        if a_axis_interval.min < b_axis_interval.min {
-            Ordering::Less
-        } else {
-            Ordering::Greater
-        }
+        a_axis_interval.min
+            .partial_cmp(&b_axis_interval.min)
+            .unwrap_or(Ordering::Equal)
     }
 
     /// Compares two hittable objects along the x-axis.
@@ -176,4 +174,30 @@ impl BVHNode {
             }
         }
     }
+
+    /// Returns the fraction of light that travels along a ray through the objects in the node.
+    ///
+    /// # Arguments
+    /// - `ray`: The ray to trace.
+    /// - `ray_t`: The valid interval for the ray parameter `t`.
+    ///
+    /// # Returns
+    /// The product of the transmittances of both children.
+    pub fn transmittance(&self, ray: &Ray, ray_t: Interval, rng: &mut ThreadRng) -> f64 {
+        if self.bounding_box().hit(ray).is_none() {
+            return 1.;
+        }
+
+        match self {
+            Self::Leaf(object) => object.transmittance(ray, ray_t, rng),
+            Self::Node { left, right, .. } => {
+                let transmittance = left.transmittance(ray, ray_t, rng);
+                if transmittance == 0. {
+                    0.
+                } else {
+                    transmittance * right.transmittance(ray, ray_t, rng)
+                }
+            }
+        }
+    }
 }
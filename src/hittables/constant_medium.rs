@@ -1,25 +1,91 @@
+use crate::materials::phase::PhaseFunction;
 use crate::prelude::*;
 
+/// A participating medium of constant density, like fog or smoke, enclosed by a boundary.
+///
+/// The boundary only needs to be closed with outward-facing normals; it may be non-convex,
+/// and rays may start inside it, since the inside is tracked from crossing to crossing.
+/// Media may overlap or nest, in which case their densities add up.
 #[derive(Debug, Clone)]
 pub struct Medium {
     boundary: Arc<Hittables>,
-    neg_inv_density: f64,
+    /// The extinction coefficient, i.e. absorption plus scattering, per unit distance.
+    density: f64,
     phase_function: Arc<Materials>,
 }
 
 impl Medium {
-    // pub fn new(boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>) -> Self {
-    //     Self {
-    //         boundary,
-    //         neg_inv_density: -1./density,
-    //         phase_function: Materials::isotropic(tex),
-    //     }
-    // }
+    /// Creates a new medium whose albedo comes from a texture.
+    ///
+    /// Scattering events happen inside the volume, so textures are looked up at the
+    /// scattering point, which suits solid textures such as noise.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `density`: The extinction coefficient per unit distance.
+    /// - `tex`: The albedo of the medium.
+    ///
+    /// # Returns
+    /// A new `Medium`.
+    pub fn new(boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>) -> Self {
+        Self::with_phase(boundary, density, tex, PhaseFunction::Isotropic)
+    }
+
+    /// Creates a new medium with a textured albedo that scatters according to a phase function.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `density`: The extinction coefficient per unit distance.
+    /// - `tex`: The albedo of the medium.
+    /// - `phase`: The angular distribution of scattered light.
+    ///
+    /// # Returns
+    /// A new `Medium`.
+    pub fn with_phase(boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>, phase: PhaseFunction) -> Self {
+        Self {
+            boundary,
+            density,
+            phase_function: Materials::volume(tex, phase),
+        }
+    }
+
+    /// Creates a new medium from physical absorption, scattering and emission coefficients.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `absorption`: The absorption coefficient `σa` per unit distance.
+    /// - `scattering`: The scattering coefficient `σs` per unit distance.
+    /// - `tex`: The color of scattered light, multiplying the albedo `σs / (σa + σs)`.
+    /// - `emission`: The radiance emitted by the absorbing part of the medium, like fire or glowing gas.
+    /// - `phase`: The angular distribution of scattered light.
+    ///
+    /// # Returns
+    /// A new `Medium`.
+    pub fn with_coefficients(
+        boundary: Arc<Hittables>,
+        absorption: f64,
+        scattering: f64,
+        tex: Arc<Textures>,
+        emission: Color3,
+        phase: PhaseFunction,
+    ) -> Self {
+        let density = absorption.max(0.) + scattering.max(0.);
+        let (albedo, absorbed) = if density > 0. {
+            (scattering.max(0.) / density, absorption.max(0.) / density)
+        } else {
+            (0., 0.)
+        };
+        Self {
+            boundary,
+            density,
+            phase_function: Materials::volume_with(tex, phase, albedo, emission * absorbed),
+        }
+    }
 
     pub fn solid(boundary: Arc<Hittables>, density: f64, color: Color3) -> Self {
         Self {
             boundary,
-            neg_inv_density: -1./density,
+            density,
             phase_function: Materials::isotropic_solid(color),
         }
     }
@@ -28,51 +94,125 @@ impl Medium {
         self.boundary.bounding_box()
     }
 
+    /// Samples the distance to the next interaction with the medium along a ray.
+    ///
+    /// Free-flight distances are exponential and memoryless, so one is sampled
+    /// independently for every part of the ray inside the boundary.
     pub fn hit(&self, ray: &Ray, interval: Interval, rng:&mut ThreadRng) -> Option<HitRecord> {
-        if let Some(mut hr1) = self.boundary.hit(ray, interval, rng) {
-            if let Some(mut hr2) = self.boundary.hit(ray, Interval::new(hr1.t+0.0001, INF), rng) {
-                if hr1.t < interval.min {hr1.t = interval.min}
-                if hr2.t > interval.max {hr2.t = interval.max}
-
-                if hr1.t >= hr2.t {
-                    None
-                } else {
-                    if hr1.t < 0. {hr1.t = 0.}
-
-                    let ray_length = ray.direction.length();
-                    let distance_inside_boundary = (hr2.t - hr1.t) * ray_length;
-                    let hit_distance = self.neg_inv_density * gen_01(rng).ln();
-                    
-                    if hit_distance > distance_inside_boundary {
-                        None
-                    } else {
-                        let t = hr1.t + hit_distance  / ray_length;
-                        let point = ray.at(t);
-                        let normal = Vec3::new(1., 0., 0.);
-                        
-                        let u = hr1.u;
-                        let v = hr2.v;
-                        let mat = self.phase_function.clone();
-                        let front_face = true;
-
-                        Some(HitRecord {
-                            point,
-                            normal,
-                            t,
-                            u,
-                            v,
-                            mat,
-                            front_face,
-                        })
-                    }
-                }
-            } else {
+        if self.density <= 0. {
+            return None;
+        }
+        let ray_length = ray.direction.length();
+
+        inside_segments(&self.boundary, ray, interval, rng, |enter, exit, uv, rng| {
+            let distance_inside_boundary = (exit - enter) * ray_length;
+            let hit_distance = -(1. - gen_01(rng)).ln() / self.density;
+
+            if hit_distance > distance_inside_boundary {
                 None
+            } else {
+                let t = enter + hit_distance / ray_length;
+                Some(scattering_record(ray, t, uv, &self.phase_function))
             }
-        } else {
+        })
+    }
+
+    /// Returns the fraction of light that crosses the medium along a ray without being scattered or absorbed.
+    ///
+    /// # Arguments
+    /// - `ray`: The ray to trace.
+    /// - `interval`: The valid interval for the ray parameter `t`.
+    ///
+    /// # Returns
+    /// The transmittance in [0, 1], computed analytically from the density.
+    pub fn transmittance(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> f64 {
+        if self.density <= 0. {
+            return 1.;
+        }
+        let mut inside = 0.;
+        inside_segments::<()>(&self.boundary, ray, interval, rng, |enter, exit, _, _| {
+            inside += exit - enter;
             None
+        });
+        (-self.density * inside * ray.direction.length()).exp()
+    }
+}
+
+/// Visits, in order, the parts of a ray that lie inside a closed boundary, until `visit` returns a value.
+///
+/// Every crossing of the boundary is found in turn. The part of the ray before a crossing is
+/// inside exactly when the ray leaves through the back face there, so this works for rays
+/// starting inside the boundary and for non-convex boundaries with many crossings.
+///
+/// # Arguments
+/// - `boundary`: The closed shape enclosing a medium, with outward-facing normals.
+/// - `ray`: The ray to trace.
+/// - `interval`: The valid interval for the ray parameter `t`.
+/// - `visit`: Called with the ray parameters where each inside part starts and ends, and the
+///   texture coordinates of the boundary where it starts, or where it ends for a ray starting
+///   inside.
+///
+/// # Returns
+/// The first value returned by `visit`, or `None` if it never returned one.
+pub(super) fn inside_segments<R>(
+    boundary: &Hittables,
+    ray: &Ray,
+    interval: Interval,
+    rng: &mut ThreadRng,
+    mut visit: impl FnMut(f64, f64, (f64, f64), &mut ThreadRng) -> Option<R>,
+) -> Option<R> {
+    let mut t = interval.min;
+    let mut entered = None;
+
+    while t < interval.max {
+        // Search past `interval.max` too: the next crossing tells whether the ray is inside,
+        // even when a closer surface cuts the ray short.
+        let crossing = boundary.hit(ray, Interval::new(t, INF), rng)?;
+        let end = crossing.t.min(interval.max);
+
+        if !crossing.front_face && end > t {
+            let uv = entered.unwrap_or((crossing.u, crossing.v));
+            if let Some(result) = visit(t, end, uv, rng) {
+                return Some(result);
+            }
         }
+        entered = crossing.front_face.then_some((crossing.u, crossing.v));
+
+        t = past_crossing(ray, &crossing);
     }
+    None
+}
 
+/// Returns a ray parameter just past a boundary crossing, from which to search for the next one.
+///
+/// The ray goes on until it is as far from the crossing, along the normal, as `HitRecord::offset_origin`
+/// would move a new ray, so the same crossing cannot be found again by rounding alone.
+pub(super) fn past_crossing(ray: &Ray, crossing: &HitRecord) -> f64 {
+    let distance = crossing.normal.abs().dot(&crossing.error);
+    let speed = ray.direction.dot(&crossing.normal).abs();
+    (crossing.t + distance / speed).next_up()
+}
 
+/// Builds the hit record of a scattering event inside a medium.
+///
+/// Volumes have no surface, so the normal simply faces back along the ray, and the texture
+/// coordinates `uv` are those of the boundary the ray entered through, so that textured media
+/// can still look their albedo up.
+pub(super) fn scattering_record(ray: &Ray, t: f64, uv: (f64, f64), phase_function: &Arc<Materials>) -> HitRecord {
+    HitRecord {
+        point: ray.at(t),
+        // Nothing is spawned from a surface here, so the point needs no offset.
+        error: Vec3::zero(),
+        normal: -1. * ray.direction.normalize(),
+        shading_normal: -1. * ray.direction.normalize(),
+        t,
+        u: uv.0,
+        v: uv.1,
+        dpdu: Vec3::zero(),
+        dpdv: Vec3::zero(),
+        duvdx: (0., 0.),
+        duvdy: (0., 0.),
+        mat: phase_function.clone(),
+        front_face: true,
+    }
 }
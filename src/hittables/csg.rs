@@ -0,0 +1,166 @@
+use crate::hittables::constant_medium::past_crossing;
+use crate::prelude::*;
+
+/// A boolean operation combining two solids.
+///
+/// # Variants
+/// - `Union`: The space inside either solid.
+/// - `Intersection`: The space inside both solids.
+/// - `Difference`: The space inside the first solid but not the second.
+#[derive(Debug, Clone, Copy)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    /// Whether a point is inside the combined solid, given whether it is inside each operand.
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Self::Union => in_left || in_right,
+            Self::Intersection => in_left && in_right,
+            Self::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// A solid built from two closed solids by a boolean operation, e.g. a lens or a hollow shell.
+///
+/// The operands may be any closed `Hittables` with outward-facing normals, including other
+/// CSG nodes. Their crossings along the ray are walked in order, tracking whether the ray is
+/// inside each one, and the first crossing where the combined inside changes is the hit.
+/// Surfaces keep the material of the operand they come from, so a cut can have its own.
+#[derive(Debug, Clone)]
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<Hittables>,
+    right: Arc<Hittables>,
+    bbox: AABB,
+}
+
+impl Csg {
+    /// Creates a new CSG node.
+    ///
+    /// # Arguments
+    /// - `operation`: How the solids are combined.
+    /// - `left`: The first solid.
+    /// - `right`: The second solid; for a `Difference`, the one cut away.
+    ///
+    /// # Returns
+    /// A new `Csg`.
+    pub fn new(operation: CsgOperation, left: Arc<Hittables>, right: Arc<Hittables>) -> Self {
+        let bbox = match operation {
+            CsgOperation::Union => AABB::from_boxes(left.bounding_box(), right.bounding_box()),
+            CsgOperation::Intersection => AABB::overlap(left.bounding_box(), right.bounding_box()),
+            CsgOperation::Difference => *left.bounding_box(),
+        };
+
+        Self { operation, left, right, bbox }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> Option<HitRecord> {
+        // Search past `interval.max` too, since whether the ray starts inside an operand is only
+        // known from the first crossing after `interval.min`.
+        let everywhere = |t: f64| Interval::new(t, INF);
+        let mut next_left = self.left.hit(ray, everywhere(interval.min), rng);
+        let mut next_right = self.right.hit(ray, everywhere(interval.min), rng);
+
+        // A ray leaving through a back face started inside.
+        let mut in_left = next_left.as_ref().is_some_and(|hr| !hr.front_face);
+        let mut in_right = next_right.as_ref().is_some_and(|hr| !hr.front_face);
+
+        loop {
+            let left_first = match (&next_left, &next_right) {
+                (None, None) => return None,
+                (Some(l), Some(r)) => l.t <= r.t,
+                (l, _) => l.is_some(),
+            };
+            let crossing = if left_first { next_left.take() } else { next_right.take() }?;
+            if crossing.t >= interval.max {
+                return None;
+            }
+
+            let was_inside = self.operation.contains(in_left, in_right);
+            if left_first {
+                in_left = crossing.front_face;
+                next_left = self.left.hit(ray, everywhere(past_crossing(ray, &crossing)), rng);
+            } else {
+                in_right = crossing.front_face;
+                next_right = self.right.hit(ray, everywhere(past_crossing(ray, &crossing)), rng);
+            }
+
+            if was_inside != self.operation.contains(in_left, in_right) {
+                let mut res = crossing;
+                // Cutting away the second solid turns its surface inside out.
+                if !left_first && matches!(self.operation, CsgOperation::Difference) {
+                    res.front_face = !res.front_face;
+                }
+                return Some(res);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two unit spheres overlapping between x = 0 and x = 1.
+    fn csg(operation: CsgOperation) -> Csg {
+        let mat = Materials::lambertian_solid(Color3::new(0.5, 0.5, 0.5));
+        Csg::new(
+            operation,
+            Hittables::new_static_sphere(Point3::new(0., 0., 0.), 1., mat.clone()),
+            Hittables::new_static_sphere(Point3::new(1., 0., 0.), 1., mat),
+        )
+    }
+
+    fn first_hit(csg: &Csg, origin: Point3, direction: Vec3) -> Option<(f64, bool)> {
+        let ray = Ray::new(origin, direction);
+        let hit = csg.hit(&ray, Interval::new(0.001, INF), &mut rand::thread_rng())?;
+        Some((hit.point.x, hit.front_face))
+    }
+
+    fn assert_hit(hit: Option<(f64, bool)>, x: f64, front_face: bool) {
+        let (hit_x, hit_front_face) = hit.expect("expected a hit");
+        assert!((hit_x - x).abs() < 1e-6, "hit at x = {hit_x}, expected {x}");
+        assert_eq!(hit_front_face, front_face);
+    }
+
+    #[test]
+    fn union_enters_at_the_first_solid() {
+        let csg = csg(CsgOperation::Union);
+        assert_hit(first_hit(&csg, Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.)), -1., true);
+        // From inside, the inner surfaces are skipped.
+        assert_hit(first_hit(&csg, Point3::new(-0.5, 0., 0.), Vec3::new(1., 0., 0.)), 2., false);
+    }
+
+    #[test]
+    fn intersection_only_hits_the_overlap() {
+        let csg = csg(CsgOperation::Intersection);
+        assert_hit(first_hit(&csg, Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.)), 0., true);
+        assert_hit(first_hit(&csg, Point3::new(5., 0., 0.), Vec3::new(-1., 0., 0.)), 1., true);
+        assert!(first_hit(&csg, Point3::new(-0.5, -5., 0.), Vec3::new(0., 1., 0.)).is_none());
+    }
+
+    #[test]
+    fn difference_turns_the_cut_inside_out() {
+        let csg = csg(CsgOperation::Difference);
+        assert_hit(first_hit(&csg, Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.)), -1., true);
+        // Leaving through the cut, and entering through it from the other side.
+        assert_hit(first_hit(&csg, Point3::new(-0.5, 0., 0.), Vec3::new(1., 0., 0.)), 0., false);
+        assert_hit(first_hit(&csg, Point3::new(5., 0., 0.), Vec3::new(-1., 0., 0.)), 0., true);
+    }
+
+    #[test]
+    fn hits_past_the_interval_are_ignored() {
+        let csg = csg(CsgOperation::Intersection);
+        let ray = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.));
+        assert!(csg.hit(&ray, Interval::new(0.001, 4.5), &mut rand::thread_rng()).is_none());
+    }
+}
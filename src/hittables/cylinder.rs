@@ -0,0 +1,306 @@
+use crate::hittables::sphere::quadratic_roots;
+use crate::prelude::*;
+
+/// An intersection found in the local frame of a shape.
+///
+/// # Fields
+/// - `t`: The ray parameter.
+/// - `point`: The local point, projected back onto the surface.
+/// - `error`: A bound on the error of each coordinate of `point`.
+/// - `normal`: The local outward normal.
+/// - `uv`: The texture coordinates.
+/// - `tangents`: The local derivatives of the point with respect to `u` and `v`.
+pub(super) struct LocalHit {
+    pub t: f64,
+    pub point: Vec3,
+    pub error: Vec3,
+    pub normal: Vec3,
+    pub uv: (f64, f64),
+    pub tangents: (Vec3, Vec3),
+}
+
+/// A circular cylinder around an arbitrary axis, open or closed by flat caps.
+///
+/// Intersections are computed in a local frame where the axis runs along `z` from
+/// `0` to `height`.
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    base: Point3,
+    basis: Onb,
+    height: f64,
+    radius: f64,
+    capped: bool,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Cylinder {
+    /// Creates a new cylinder.
+    ///
+    /// # Arguments
+    /// - `base`: The center of the bottom end.
+    /// - `axis`: The vector from the bottom end to the top end.
+    /// - `radius`: The radius of the cylinder.
+    /// - `capped`: Whether the ends are closed by disks.
+    /// - `mat`: The material of the cylinder.
+    ///
+    /// # Returns
+    /// A new `Cylinder`. On the side, `u` is the angle around the axis and `v` the height,
+    /// both in [0, 1]; on the caps, `u` is the angle and `v` the distance from the axis.
+    pub fn new(base: Point3, axis: Vec3, radius: f64, capped: bool, mat: Arc<Materials>) -> Self {
+        let top = base + axis;
+        let extent = disk_extent(&axis, radius);
+        let mut bbox = AABB::from_boxes(
+            &AABB::from_points(base - extent, base + extent),
+            &AABB::from_points(top - extent, top + extent),
+        );
+        bbox.pad_to_minimums();
+
+        Self {
+            base,
+            basis: Onb::new(&axis),
+            height: axis.length(),
+            radius,
+            capped,
+            mat,
+            bbox,
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let origin = self.basis.inverse_transform(&(ray.origin - self.base));
+        let direction = self.basis.inverse_transform(&ray.direction);
+        let mut closest: Option<LocalHit> = None;
+        let mut consider = |hit: LocalHit| {
+            if interval.surrounds(hit.t) && closest.as_ref().is_none_or(|best| hit.t < best.t) {
+                closest = Some(hit);
+            }
+        };
+
+        // The side, where x^2 + y^2 = r^2.
+        let a = direction.x * direction.x + direction.y * direction.y;
+        let h = -(origin.x * direction.x + origin.y * direction.y);
+        let c = origin.x * origin.x + origin.y * origin.y - self.radius * self.radius;
+        let discriminant = h * h - a * c;
+        if a > 0. && discriminant >= 0. {
+            for t in quadratic_roots(a, h, c, discriminant) {
+                let mut p = origin + t * direction;
+                if (0. ..=self.height).contains(&p.z) {
+                    // Only the distance from the axis matters to the normal, so project it back onto the radius.
+                    let scale = self.radius / (p.x * p.x + p.y * p.y).sqrt();
+                    p.x *= scale;
+                    p.y *= scale;
+                    consider(LocalHit {
+                        t,
+                        point: p,
+                        error: gamma(3) * Vec3::new(p.x, p.y, 0.).abs(),
+                        normal: Vec3::new(p.x, p.y, 0.) / self.radius,
+                        uv: (angle_uv(&p), p.z / self.height),
+                        tangents: (angle_tangent(&p), Vec3::new(0., 0., self.height)),
+                    });
+                }
+            }
+        }
+
+        if self.capped && direction.z != 0. {
+            for (z, normal) in [(0., Vec3::new(0., 0., -1.)), (self.height, Vec3::new(0., 0., 1.))] {
+                let t = (z - origin.z) / direction.z;
+                if let Some(hit) = cap_hit(origin + t * direction, z, t, normal, self.radius) {
+                    consider(hit);
+                }
+            }
+        }
+
+        Some(local_hit_record(ray, closest?, (&self.base, &self.basis), &self.mat))
+    }
+}
+
+/// A circular cone around an arbitrary axis, narrowing from its base to its apex.
+#[derive(Debug, Clone)]
+pub struct Cone {
+    base: Point3,
+    basis: Onb,
+    height: f64,
+    radius: f64,
+    capped: bool,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Cone {
+    /// Creates a new cone.
+    ///
+    /// # Arguments
+    /// - `base`: The center of the base.
+    /// - `axis`: The vector from the center of the base to the apex.
+    /// - `radius`: The radius of the base.
+    /// - `capped`: Whether the base is closed by a disk.
+    /// - `mat`: The material of the cone.
+    ///
+    /// # Returns
+    /// A new `Cone`, with UVs laid out like those of a `Cylinder`.
+    pub fn new(base: Point3, axis: Vec3, radius: f64, capped: bool, mat: Arc<Materials>) -> Self {
+        let extent = disk_extent(&axis, radius);
+        let mut bbox = AABB::from_boxes(
+            &AABB::from_points(base - extent, base + extent),
+            &AABB::from_points(base + axis, base + axis),
+        );
+        bbox.pad_to_minimums();
+
+        Self {
+            base,
+            basis: Onb::new(&axis),
+            height: axis.length(),
+            radius,
+            capped,
+            mat,
+            bbox,
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let origin = self.basis.inverse_transform(&(ray.origin - self.base));
+        let direction = self.basis.inverse_transform(&ray.direction);
+        let mut closest: Option<LocalHit> = None;
+        let mut consider = |hit: LocalHit| {
+            if interval.surrounds(hit.t) && closest.as_ref().is_none_or(|best| hit.t < best.t) {
+                closest = Some(hit);
+            }
+        };
+
+        // The side, where x^2 + y^2 = (k * (height - z))^2 with k = radius / height.
+        let k2 = (self.radius / self.height).powi(2);
+        let rest = self.height - origin.z;
+        let a = direction.x * direction.x + direction.y * direction.y - k2 * direction.z * direction.z;
+        let h = -(origin.x * direction.x + origin.y * direction.y + k2 * rest * direction.z);
+        let c = origin.x * origin.x + origin.y * origin.y - k2 * rest * rest;
+
+        let roots = if a.abs() < 1e-12 {
+            // The ray is parallel to the slope, so it crosses the double cone only once.
+            if h == 0. { vec![] } else { vec![c / (2. * h)] }
+        } else {
+            let discriminant = h * h - a * c;
+            if discriminant < 0. {
+                vec![]
+            } else {
+                quadratic_roots(a, h, c, discriminant).to_vec()
+            }
+        };
+        for t in roots {
+            let mut p = origin + t * direction;
+            if (0. ..=self.height).contains(&p.z) {
+                // Project the point back onto the side, at the radius the cone has at its height.
+                let distance = (p.x * p.x + p.y * p.y).sqrt();
+                if distance > 0. {
+                    let scale = k2.sqrt() * (self.height - p.z) / distance;
+                    p.x *= scale;
+                    p.y *= scale;
+                }
+                let normal = Vec3::new(p.x, p.y, k2 * (self.height - p.z)).normalize();
+                // Up the side, the point moves towards the axis as it climbs.
+                let inwards = if distance > 0. { -self.radius / distance * Vec3::new(p.x, p.y, 0.) } else { Vec3::zero() };
+                consider(LocalHit {
+                    t,
+                    point: p,
+                    error: gamma(7) * p.abs(),
+                    normal,
+                    uv: (angle_uv(&p), p.z / self.height),
+                    tangents: (angle_tangent(&p), inwards + Vec3::new(0., 0., self.height)),
+                });
+            }
+        }
+
+        if self.capped && direction.z != 0. {
+            let t = -origin.z / direction.z;
+            if let Some(hit) = cap_hit(origin + t * direction, 0., t, Vec3::new(0., 0., -1.), self.radius) {
+                consider(hit);
+            }
+        }
+
+        Some(local_hit_record(ray, closest?, (&self.base, &self.basis), &self.mat))
+    }
+}
+
+/// The half-extent, along each world axis, of a disk of `radius` perpendicular to `axis`.
+pub(super) fn disk_extent(axis: &Vec3, radius: f64) -> Vec3 {
+    let a = axis.normalize();
+    Vec3::new(
+        radius * (1. - a.x * a.x).max(0.).sqrt(),
+        radius * (1. - a.y * a.y).max(0.).sqrt(),
+        radius * (1. - a.z * a.z).max(0.).sqrt(),
+    )
+}
+
+/// The angle of a local point around the `z` axis, mapped to [0, 1].
+pub(super) fn angle_uv(p: &Vec3) -> f64 {
+    (p.y.atan2(p.x) + PI) / (2. * PI)
+}
+
+/// The derivative of a local point with respect to `angle_uv`.
+pub(super) fn angle_tangent(p: &Vec3) -> Vec3 {
+    2. * PI * Vec3::new(-p.y, p.x, 0.)
+}
+
+/// The tangents of a cap of `radius`, whose UVs are the angle and the distance from the center.
+fn cap_tangents(p: &Vec3, radius: f64) -> (Vec3, Vec3) {
+    let distance = (p.x * p.x + p.y * p.y).sqrt();
+    let outwards = if distance > 0. { radius / distance * Vec3::new(p.x, p.y, 0.) } else { Vec3::zero() };
+    (angle_tangent(p), outwards)
+}
+
+/// The intersection with a cap of `radius` lying in the plane `z`, if the local point `p` is on it.
+fn cap_hit(mut p: Vec3, z: f64, t: f64, normal: Vec3, radius: f64) -> Option<LocalHit> {
+    let distance = (p.x * p.x + p.y * p.y).sqrt();
+    if distance > radius {
+        return None;
+    }
+    // The cap is flat, so the height of the point is known exactly.
+    p.z = z;
+    Some(LocalHit {
+        t,
+        point: p,
+        error: Vec3::zero(),
+        normal,
+        uv: (angle_uv(&p), distance / radius),
+        tangents: cap_tangents(&p, radius),
+    })
+}
+
+/// Builds the hit record of a shape intersected in a local frame.
+///
+/// # Arguments
+/// - `hit`: The intersection, in the local frame.
+/// - `(origin, basis)`: The local frame of the shape.
+pub(super) fn local_hit_record(ray: &Ray, hit: LocalHit, (origin, basis): (&Point3, &Onb), mat: &Arc<Materials>) -> HitRecord {
+    let outward_normal = basis.transform(&hit.normal);
+    let point = *origin + basis.transform(&hit.point);
+    // Moving the point into place, and the origins of later rays back into the local frame,
+    // round a few more times.
+    let error = basis.transform_abs(&(hit.error + gamma(6) * hit.point.abs())) + gamma(2) * point.abs();
+    let (u, v) = hit.uv;
+    let mut res = HitRecord {
+        point,
+        error,
+        normal: outward_normal,
+        shading_normal: outward_normal,
+        t: hit.t,
+        mat: mat.clone(),
+        front_face: false,
+        u,
+        v,
+        dpdu: basis.transform(&hit.tangents.0),
+        dpdv: basis.transform(&hit.tangents.1),
+        duvdx: (0., 0.),
+        duvdy: (0., 0.),
+    };
+    res.set_face_normal(ray, &outward_normal);
+    res
+}
@@ -0,0 +1,240 @@
+use std::io::{BufReader, ErrorKind, Read};
+
+use crate::hittables::constant_medium::{inside_segments, scattering_record};
+use crate::materials::phase::PhaseFunction;
+use crate::perlin::Perlin;
+use crate::prelude::*;
+
+/// The magic bytes at the start of a density grid file.
+const GRID_MAGIC: &[u8; 4] = b"DENS";
+
+/// A regular 3D grid of density values stretched over the bounding box of a medium.
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    /// The number of voxels along each axis.
+    dims: [usize; 3],
+    /// The voxel values, with x varying fastest, then y, then z.
+    values: Vec<f32>,
+    /// The largest value in the grid.
+    max: f64,
+}
+
+impl DensityGrid {
+    /// Loads a density grid from a raw binary file.
+    ///
+    /// The file starts with the four bytes `DENS`, followed by the number of voxels
+    /// along x, y and z as little-endian `u32`s, followed by one little-endian `f32`
+    /// per voxel, with x varying fastest, then y, then z.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the grid file.
+    ///
+    /// # Returns
+    /// The grid, or an error if the file cannot be read or is malformed.
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, format!("{path}: {message}"));
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != GRID_MAGIC {
+            return Err(invalid("not a density grid"));
+        }
+
+        let mut dims = [0usize; 3];
+        for dim in &mut dims {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            *dim = u32::from_le_bytes(bytes) as usize;
+        }
+        if dims.contains(&0) {
+            return Err(invalid("grid has no voxels"));
+        }
+
+        let mut bytes = vec![0u8; dims[0] * dims[1] * dims[2] * 4];
+        reader.read_exact(&mut bytes)?;
+        let values: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]).max(0.))
+            .collect();
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(invalid("grid contains non-finite densities"));
+        }
+        let max = values.iter().fold(0f32, |a, &b| a.max(b)) as f64;
+
+        Ok(Self { dims, values, max })
+    }
+
+    /// Returns the value of a voxel, or zero outside the grid.
+    fn voxel(&self, x: isize, y: isize, z: isize) -> f64 {
+        let [nx, ny, nz] = self.dims;
+        if x < 0 || y < 0 || z < 0 || x >= nx as isize || y >= ny as isize || z >= nz as isize {
+            return 0.;
+        }
+        self.values[x as usize + nx * (y as usize + ny * z as usize)] as f64
+    }
+
+    /// Trilinearly interpolates the grid at a point given in [0, 1] over each axis.
+    fn value(&self, p: &Vec3) -> f64 {
+        let mut cell = [0isize; 3];
+        let mut frac = [0f64; 3];
+        for axis in 0..3 {
+            let x = p[axis] * self.dims[axis] as f64 - 0.5;
+            cell[axis] = x.floor() as isize;
+            frac[axis] = x - x.floor();
+        }
+
+        let mut accum = 0.;
+        for (i, wx) in [(0, 1. - frac[0]), (1, frac[0])] {
+            for (j, wy) in [(0, 1. - frac[1]), (1, frac[1])] {
+                for (k, wz) in [(0, 1. - frac[2]), (1, frac[2])] {
+                    accum += wx * wy * wz * self.voxel(cell[0] + i, cell[1] + j, cell[2] + k);
+                }
+            }
+        }
+        accum
+    }
+}
+
+/// Where the density of a heterogeneous medium comes from.
+///
+/// # Variants
+/// - `Grid`: A voxel grid stretched over the bounding box of the boundary.
+/// - `Noise`: Perlin turbulence, clamped to [0, 1], sampled at `scale` times the point.
+#[derive(Debug, Clone)]
+pub enum DensityField {
+    Grid(DensityGrid),
+    Noise { noise: Box<Perlin>, scale: f64 },
+}
+
+impl DensityField {
+    /// Returns the largest value the field can take.
+    fn max(&self) -> f64 {
+        match self {
+            Self::Grid(grid) => grid.max,
+            Self::Noise { .. } => 1.,
+        }
+    }
+}
+
+/// A participating medium, like a cloud or a smoke plume, whose density varies through space.
+///
+/// Free-flight distances are sampled with delta tracking and shadow rays are attenuated
+/// with ratio tracking, both against the maximum density, so the result is unbiased.
+#[derive(Debug, Clone)]
+pub struct HeterogeneousMedium {
+    boundary: Arc<Hittables>,
+    field: DensityField,
+    /// The extinction coefficient where the field equals one.
+    density: f64,
+    /// An upper bound on the extinction coefficient anywhere in the medium.
+    majorant: f64,
+    phase_function: Arc<Materials>,
+}
+
+impl HeterogeneousMedium {
+    /// Creates a new heterogeneous medium.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `field`: The spatially varying density.
+    /// - `density`: The extinction coefficient per unit distance where the field equals one.
+    /// - `tex`: The albedo of the medium.
+    /// - `phase`: The angular distribution of scattered light.
+    ///
+    /// # Returns
+    /// A new `HeterogeneousMedium`.
+    pub fn new(boundary: Arc<Hittables>, field: DensityField, density: f64, tex: Arc<Textures>, phase: PhaseFunction) -> Self {
+        let majorant = density * field.max();
+        Self {
+            boundary,
+            field,
+            density,
+            majorant,
+            phase_function: Materials::volume(tex, phase),
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        self.boundary.bounding_box()
+    }
+
+    /// Returns the extinction coefficient at a point.
+    fn density_at(&self, p: &Point3) -> f64 {
+        let value = match &self.field {
+            DensityField::Grid(grid) => {
+                let bbox = self.boundary.bounding_box();
+                let mut local = Vec3::zero();
+                for axis in 0..3 {
+                    let extent = bbox.axis_interval(axis as i32);
+                    local[axis] = (p[axis] - extent.min) / extent.size();
+                }
+                grid.value(&local)
+            }
+            DensityField::Noise { noise, scale } => noise.turb(&(*scale * *p), 7).min(1.),
+        };
+        self.density * value
+    }
+
+    /// Samples a distance along the ray with delta tracking.
+    ///
+    /// Tentative collisions are drawn against the majorant and accepted with probability
+    /// `density / majorant`; rejected ones are null collisions and tracking continues.
+    pub fn hit(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> Option<HitRecord> {
+        if self.majorant <= 0. {
+            return None;
+        }
+        let ray_length = ray.direction.length();
+
+        inside_segments(&self.boundary, ray, interval, rng, |enter, exit, uv, rng| {
+            let mut t = enter;
+            loop {
+                t -= (1. - gen_01(rng)).ln() / (self.majorant * ray_length);
+                if t >= exit {
+                    return None;
+                }
+                if gen_01(rng) * self.majorant < self.density_at(&ray.at(t)) {
+                    return Some(scattering_record(ray, t, uv, &self.phase_function));
+                }
+            }
+        })
+    }
+
+    /// Estimates the fraction of light that crosses the medium along a ray with ratio tracking.
+    ///
+    /// # Arguments
+    /// - `ray`: The ray to trace.
+    /// - `interval`: The valid interval for the ray parameter `t`.
+    /// - `rng`: A random number generator.
+    ///
+    /// # Returns
+    /// An unbiased estimate of the transmittance, in [0, 1] up to Russian roulette.
+    pub fn transmittance(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> f64 {
+        if self.majorant <= 0. {
+            return 1.;
+        }
+        let ray_length = ray.direction.length();
+        let mut transmittance = 1.;
+
+        inside_segments(&self.boundary, ray, interval, rng, |enter, exit, _, rng| {
+            let mut t = enter;
+            loop {
+                t -= (1. - gen_01(rng)).ln() / (self.majorant * ray_length);
+                if t >= exit {
+                    return None;
+                }
+                transmittance *= 1. - self.density_at(&ray.at(t)) / self.majorant;
+
+                // Stop tracking rays that are almost fully attenuated without biasing the estimate.
+                if transmittance < 0.1 {
+                    if gen_01(rng) < 0.5 {
+                        transmittance = 0.;
+                        return Some(());
+                    }
+                    transmittance *= 2.;
+                }
+            }
+        });
+        transmittance
+    }
+}
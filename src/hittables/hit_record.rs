@@ -4,17 +4,34 @@ use crate::prelude::*;
 ///
 /// # Fields
 /// - `point`: The point of intersection.
-/// - `normal`: The normal vector at the intersection point.
+/// - `error`: A conservative bound on the floating-point error of each coordinate of `point`;
+///   the exact intersection lies within this box around it.
+/// - `normal`: The geometric normal at the intersection point, facing the incoming ray. It decides
+///   which side of the surface a direction is on, so new rays start on the correct side.
+/// - `shading_normal`: The normal materials shade with, facing the same side as `normal`. It
+///   differs from `normal` where interpolated vertex normals or bump maps bend it.
 /// - `t`: The ray parameter at the intersection.
+/// - `u`, `v`: The texture coordinates of the intersection.
+/// - `dpdu`, `dpdv`: How the point moves as `u` and `v` change, i.e. the surface tangents
+///   along the texture axes, used to orient normal and bump maps. Zero where the surface
+///   has no parametrization, like inside media, or at singular points like the poles of a sphere.
+/// - `duvdx`, `duvdy`: How `u` and `v` change from one pixel to the next horizontally and
+///   vertically, i.e. the footprint textures filter over. Zero for rays without differentials.
 /// - `mat`: The material of the surface hit.
 /// - `front_face`: A boolean indicating if the ray hit the front face of the surface.
 #[derive(Debug, Clone)]
 pub struct HitRecord {
     pub point: Point3,
+    pub error: Vec3,
     pub normal: Vec3,
+    pub shading_normal: Vec3,
     pub t: f64,
     pub u: f64,
     pub v: f64,
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    pub duvdx: (f64, f64),
+    pub duvdy: (f64, f64),
     pub mat: Arc<Materials>,
     pub front_face: bool,
 }
@@ -28,13 +45,114 @@ impl HitRecord {
     ///
     /// # Details
     /// This method determines whether the ray hit the front face or the back face of the surface
-    /// and adjusts the normal direction accordingly.
+    /// and adjusts the normal direction accordingly. The shading normal is reset to the same normal.
     pub fn set_face_normal(&mut self, ray: &Ray, outward_normal: &Vec3) {
         self.front_face = ray.direction.dot(outward_normal) < 0.0;
         self.normal = if self.front_face {
             *outward_normal
         } else {
             -1.0 * *outward_normal
+        };
+        self.shading_normal = self.normal;
+    }
+
+    /// Sets the shading normal, facing the same side of the surface as the geometric normal.
+    ///
+    /// # Arguments
+    /// - `outward_normal`: The outward shading normal, e.g. interpolated from vertex normals.
+    ///
+    /// # Details
+    /// Must be called after `set_face_normal`, which decides the side the ray hit.
+    pub fn set_shading_normal(&mut self, outward_normal: &Vec3) {
+        self.shading_normal = if self.front_face {
+            *outward_normal
+        } else {
+            -1.0 * *outward_normal
+        };
+    }
+
+    /// Sets the texture footprint of the hit from the differentials of the ray that found it.
+    ///
+    /// # Arguments
+    /// - `ray`: The ray that hit the surface, in world space like the hit record.
+    ///
+    /// # Details
+    /// The differential rays are intersected with the tangent plane, and the offsets of those
+    /// points from the hit are expressed in the tangents `dpdu` and `dpdv` by least squares.
+    pub fn set_differentials(&mut self, ray: &Ray) {
+        self.duvdx = (0., 0.);
+        self.duvdy = (0., 0.);
+        let Some(d) = &ray.differentials else { return };
+
+        let distance = self.normal.dot(&self.point);
+        let on_plane = |origin: &Point3, direction: &Vec3| {
+            let t = (distance - self.normal.dot(origin)) / self.normal.dot(direction);
+            *origin + t * *direction - self.point
+        };
+        let dpdx = on_plane(&d.rx_origin, &d.rx_direction);
+        let dpdy = on_plane(&d.ry_origin, &d.ry_direction);
+
+        let (uu, uv, vv) = (self.dpdu.dot(&self.dpdu), self.dpdu.dot(&self.dpdv), self.dpdv.dot(&self.dpdv));
+        let det = uu * vv - uv * uv;
+        if det.abs() < 1e-20 {
+            return;
+        }
+        let solve = |dp: Vec3| {
+            let (pu, pv) = (self.dpdu.dot(&dp), self.dpdv.dot(&dp));
+            let du = (vv * pu - uv * pv) / det;
+            let dv = (uu * pv - uv * pu) / det;
+            // Grazing differential rays meet the plane far away, or not at all.
+            if du.is_finite() && dv.is_finite() { (du.clamp(-1e8, 1e8), dv.clamp(-1e8, 1e8)) } else { (0., 0.) }
+        };
+        self.duvdx = solve(dpdx);
+        self.duvdy = solve(dpdy);
+    }
+
+    /// Spawns a ray leaving the surface, from an origin offset past the error bounds of the
+    /// hit point so that the ray cannot hit the same surface again by rounding alone.
+    ///
+    /// # Arguments
+    /// - `direction`: The direction of the new ray.
+    /// - `time`: The time of the new ray.
+    ///
+    /// # Returns
+    /// A `Ray` that can be traced from `t = 0`.
+    pub fn spawn_ray(&self, direction: Vec3, time: f64) -> Ray {
+        Ray::new_time(self.offset_origin(&direction), direction, time)
+    }
+
+    /// Offsets the hit point along the geometric normal, to the side `direction` leaves on, far
+    /// enough that the whole error box lies behind the new origin.
+    ///
+    /// # Arguments
+    /// - `direction`: The direction of the ray to spawn.
+    ///
+    /// # Returns
+    /// The offset origin, rounded away from the hit point so the offset itself cannot round back.
+    pub fn offset_origin(&self, direction: &Vec3) -> Point3 {
+        let distance = self.normal.abs().dot(&self.error);
+        let mut offset = distance * self.normal;
+        if direction.dot(&self.normal) < 0. {
+            offset = -1.0 * offset;
+        }
+
+        let mut origin = self.point + offset;
+        for axis in 0..3 {
+            if offset[axis] > 0. {
+                origin[axis] = origin[axis].next_up();
+            } else if offset[axis] < 0. {
+                origin[axis] = origin[axis].next_down();
+            }
+        }
+        origin
+    }
+
+    /// Returns the outward shading normal, regardless of the side the ray hit.
+    pub fn outward_shading_normal(&self) -> Vec3 {
+        if self.front_face {
+            self.shading_normal
+        } else {
+            -1.0 * self.shading_normal
         }
     }
 }
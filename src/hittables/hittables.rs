@@ -1,8 +1,17 @@
-use crate::hittables::sphere::Sphere;
+use crate::hittables::sphere::{Ellipsoid, Sphere};
 use crate::hittables::quad::Quad;
+use crate::hittables::planar::{Ellipse, Plane, Triangle};
+use crate::hittables::cylinder::{Cone, Cylinder};
+use crate::hittables::torus::Torus;
+use crate::hittables::sdf::{Sdf, SdfSurface};
 use crate::hittables::bvh::BVHNode;
+use crate::hittables::csg::{Csg, CsgOperation};
+use crate::hittables::mesh::Mesh;
 use crate::hittables::translation::Translate;
 use crate::hittables::constant_medium::Medium;
+use crate::hittables::heterogeneous_medium::{DensityField, DensityGrid, HeterogeneousMedium};
+use crate::materials::phase::PhaseFunction;
+use crate::perlin::Perlin;
 use crate::prelude::*;
 
 use super::translation::RotateY;
@@ -11,15 +20,32 @@ use super::translation::RotateY;
 ///
 /// # Variants
 /// - `Sphere`: A hittable sphere.
+/// - `Ellipsoid`: A sphere with a different radius along each of three perpendicular axes.
+/// - `Triangle`, `Ellipse`, `Plane`: Flat shapes sharing the plane intersection of `Quad`;
+///   disks are ellipses with equal axes.
+/// - `Cylinder`, `Cone`, `Torus`: Analytic curved surfaces around an arbitrary axis.
+/// - `Sdf`: A shape built from signed distance functions and rendered by sphere tracing.
+/// - `Csg`: A boolean combination of two closed shapes.
 #[derive(Debug, Clone)]
 pub enum Hittables {
     Sphere(Sphere),
+    Ellipsoid(Ellipsoid),
+    #[allow(clippy::upper_case_acronyms)]
     BVH(Box<BVHNode>),
     List(Box<HittableList>),
     Quad(Quad),
+    Triangle(Triangle),
+    Ellipse(Ellipse),
+    Plane(Plane),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Torus(Torus),
+    Sdf(SdfSurface),
     Translate(Translate),
     RotY(RotateY),
+    Csg(Csg),
     Medium(Medium),
+    HeterogeneousMedium(HeterogeneousMedium),
 }
 
 impl Hittables {
@@ -50,10 +76,177 @@ impl Hittables {
         Arc::new(Self::Sphere(Sphere::new_moving(start, end, radius, mat)))
     }
 
+    /// Creates a new static ellipsoid.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ellipsoid.
+    /// - `radii`: The radii along the `right`, `up` and `right x up` directions.
+    /// - `right`: The direction of the first radius.
+    /// - `up`: The direction of the second radius.
+    /// - `mat`: The material of the ellipsoid.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the static ellipsoid.
+    pub fn new_static_ellipsoid(center: Point3, radii: Vec3, right: Vec3, up: Vec3, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Ellipsoid(Ellipsoid::new_static(center, radii, right, up, mat)))
+    }
+
+    /// Creates a new moving ellipsoid.
+    ///
+    /// # Arguments
+    /// - `start`: The center of the ellipsoid at time 0.
+    /// - `end`: The center of the ellipsoid at time 1.
+    /// - `radii`: The radii along the `right`, `up` and `right x up` directions.
+    /// - `right`: The direction of the first radius.
+    /// - `up`: The direction of the second radius.
+    /// - `mat`: The material of the ellipsoid.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the moving ellipsoid.
+    pub fn new_moving_ellipsoid(start: Point3, end: Point3, radii: Vec3, right: Vec3, up: Vec3, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Ellipsoid(Ellipsoid::new_moving(start, end, radii, right, up, mat)))
+    }
+
     pub fn new_quad(q : Point3, u : Vec3, v : Vec3, mat : Arc<Materials>) -> Arc<Self> {
         Arc::new(Self::Quad(Quad::new(q, u, v, mat)))
     }
 
+    /// Creates a new triangle.
+    ///
+    /// # Arguments
+    /// - `a`, `b`, `c`: The corners, counter-clockwise around the front face.
+    /// - `mat`: The material of the triangle.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the triangle.
+    pub fn new_triangle(a: Point3, b: Point3, c: Point3, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Triangle(Triangle::new(a, b, c, mat)))
+    }
+
+    /// Creates a new triangle with texture coordinates given at its corners.
+    ///
+    /// # Arguments
+    /// - `a`, `b`, `c`: The corners, counter-clockwise around the front face.
+    /// - `uvs`: The texture coordinates at `a`, `b` and `c`.
+    /// - `mat`: The material of the triangle.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the triangle.
+    pub fn new_triangle_with_uvs(a: Point3, b: Point3, c: Point3, uvs: [(f64, f64); 3], mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Triangle(Triangle::with_uvs(a, b, c, uvs, mat)))
+    }
+
+    /// Creates a new triangle shaded with normals interpolated from its corners.
+    ///
+    /// # Arguments
+    /// - `a`, `b`, `c`: The corners, counter-clockwise around the front face.
+    /// - `normals`: The outward shading normals at `a`, `b` and `c`.
+    /// - `uvs`: The texture coordinates at `a`, `b` and `c`.
+    /// - `mat`: The material of the triangle.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the triangle.
+    pub fn new_smooth_triangle(a: Point3, b: Point3, c: Point3, normals: [Vec3; 3], uvs: [(f64, f64); 3], mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Triangle(Triangle::with_normals(a, b, c, normals, uvs, mat)))
+    }
+
+    /// Creates a new ellipse.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ellipse.
+    /// - `u`, `v`: The two semi-axes; the front face is the side `u x v` points to.
+    /// - `mat`: The material of the ellipse.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the ellipse.
+    pub fn new_ellipse(center: Point3, u: Vec3, v: Vec3, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Ellipse(Ellipse::new(center, u, v, mat)))
+    }
+
+    /// Creates a new disk.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the disk.
+    /// - `normal`: The direction the front face points to.
+    /// - `radius`: The radius of the disk.
+    /// - `mat`: The material of the disk.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the disk.
+    pub fn new_disk(center: Point3, normal: Vec3, radius: f64, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Ellipse(Ellipse::disk(center, normal, radius, mat)))
+    }
+
+    /// Creates a new infinite plane.
+    ///
+    /// # Arguments
+    /// - `point`: Any point on the plane.
+    /// - `normal`: The direction the front face points to.
+    /// - `mat`: The material of the plane.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the plane.
+    pub fn new_plane(point: Point3, normal: Vec3, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Plane(Plane::new(point, normal, mat)))
+    }
+
+    /// Creates a new cylinder.
+    ///
+    /// # Arguments
+    /// - `base`: The center of the bottom end.
+    /// - `axis`: The vector from the bottom end to the top end.
+    /// - `radius`: The radius of the cylinder.
+    /// - `capped`: Whether the ends are closed by disks.
+    /// - `mat`: The material of the cylinder.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the cylinder.
+    pub fn new_cylinder(base: Point3, axis: Vec3, radius: f64, capped: bool, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Cylinder(Cylinder::new(base, axis, radius, capped, mat)))
+    }
+
+    /// Creates a new cone.
+    ///
+    /// # Arguments
+    /// - `base`: The center of the base.
+    /// - `axis`: The vector from the center of the base to the apex.
+    /// - `radius`: The radius of the base.
+    /// - `capped`: Whether the base is closed by a disk.
+    /// - `mat`: The material of the cone.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the cone.
+    pub fn new_cone(base: Point3, axis: Vec3, radius: f64, capped: bool, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Cone(Cone::new(base, axis, radius, capped, mat)))
+    }
+
+    /// Creates a new torus.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ring.
+    /// - `axis`: The direction the ring is swept around.
+    /// - `major_radius`: The distance from the center to the middle of the tube.
+    /// - `minor_radius`: The radius of the tube.
+    /// - `mat`: The material of the torus.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the torus.
+    pub fn new_torus(center: Point3, axis: Vec3, major_radius: f64, minor_radius: f64, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Torus(Torus::new(center, axis, major_radius, minor_radius, mat)))
+    }
+
+    /// Creates a new signed distance field surface.
+    ///
+    /// # Arguments
+    /// - `sdf`: The distance function, which must be bounded.
+    /// - `mat`: The material of the surface.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the surface.
+    pub fn new_sdf(sdf: Sdf, mat: Arc<Materials>) -> Arc<Self> {
+        Arc::new(Self::Sdf(SdfSurface::new(sdf, mat)))
+    }
+
     pub fn translate(object: Arc<Self>, offset: Vec3) -> Arc<Self> {
         Arc::new(Self::Translate(Translate::new(object, offset)))
     }
@@ -62,14 +255,132 @@ impl Hittables {
         Arc::new(Self::RotY(RotateY::new(object, degree)))
     }
 
-    // pub fn new_medium(boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>) -> Arc<Self> {
-    //     Arc::new(Self::Medium(Medium::new(boundary ,density, tex)))
-    // }
+    /// Creates the union of two closed shapes.
+    ///
+    /// # Arguments
+    /// - `a`, `b`: The shapes to combine.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance enclosing the space inside either shape.
+    #[allow(dead_code)]
+    pub fn union(a: Arc<Self>, b: Arc<Self>) -> Arc<Self> {
+        Arc::new(Self::Csg(Csg::new(CsgOperation::Union, a, b)))
+    }
+
+    /// Creates the intersection of two closed shapes, e.g. a lens from two spheres.
+    ///
+    /// # Arguments
+    /// - `a`, `b`: The shapes to combine.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance enclosing the space inside both shapes.
+    pub fn intersection(a: Arc<Self>, b: Arc<Self>) -> Arc<Self> {
+        Arc::new(Self::Csg(Csg::new(CsgOperation::Intersection, a, b)))
+    }
+
+    /// Cuts one closed shape out of another, e.g. a hole through a box.
+    ///
+    /// # Arguments
+    /// - `a`: The shape to cut.
+    /// - `b`: The shape to cut away; its material shows on the cut surfaces.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance enclosing the space inside `a` but not `b`.
+    pub fn difference(a: Arc<Self>, b: Arc<Self>) -> Arc<Self> {
+        Arc::new(Self::Csg(Csg::new(CsgOperation::Difference, a, b)))
+    }
+
+    /// Creates a new constant-density medium with a textured albedo.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `density`: The extinction coefficient per unit distance.
+    /// - `tex`: The albedo of the medium.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the medium.
+    pub fn new_medium(boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>) -> Arc<Self> {
+        Arc::new(Self::Medium(Medium::new(boundary ,density, tex)))
+    }
+
+    /// Creates a new constant-density medium that scatters according to a phase function.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `density`: The extinction coefficient per unit distance.
+    /// - `tex`: The albedo of the medium.
+    /// - `phase`: The angular distribution of scattered light.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the medium.
+    pub fn new_medium_with(boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>, phase: PhaseFunction) -> Arc<Self> {
+        Arc::new(Self::Medium(Medium::with_phase(boundary, density, tex, phase)))
+    }
+
+    /// Creates a new constant medium from absorption, scattering and emission coefficients.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `absorption`: The absorption coefficient per unit distance.
+    /// - `scattering`: The scattering coefficient per unit distance.
+    /// - `tex`: The color of scattered light.
+    /// - `emission`: The radiance emitted by the absorbing part of the medium.
+    /// - `phase`: The angular distribution of scattered light.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the medium.
+    #[allow(dead_code)]
+    pub fn new_medium_with_coefficients(
+        boundary: Arc<Hittables>,
+        absorption: f64,
+        scattering: f64,
+        tex: Arc<Textures>,
+        emission: Color3,
+        phase: PhaseFunction,
+    ) -> Arc<Self> {
+        Arc::new(Self::Medium(Medium::with_coefficients(boundary, absorption, scattering, tex, emission, phase)))
+    }
 
     pub fn new_solid_medium(boundary: Arc<Hittables>, density: f64, albedo: Color3) -> Arc<Self> {
         Arc::new(Self::Medium(Medium::solid(boundary ,density, albedo)))
     }
 
+    /// Creates a new medium whose density is read from a voxel grid file.
+    ///
+    /// The grid is stretched over the bounding box of the boundary.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `path`: The path to the density grid, in the format read by `DensityGrid::load`.
+    /// - `density`: The extinction coefficient per unit distance where the grid equals one.
+    /// - `tex`: The albedo of the medium.
+    /// - `phase`: The angular distribution of scattered light.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the medium, or an error if the grid cannot be loaded.
+    #[allow(dead_code)]
+    pub fn new_grid_medium(boundary: Arc<Hittables>, path: &str, density: f64, tex: Arc<Textures>, phase: PhaseFunction) -> Result<Arc<Self>, Error> {
+        let field = DensityField::Grid(DensityGrid::load(path)?);
+        Ok(Arc::new(Self::HeterogeneousMedium(HeterogeneousMedium::new(boundary, field, density, tex, phase))))
+    }
+
+    /// Creates a new medium whose density follows Perlin turbulence, like a cloud.
+    ///
+    /// # Arguments
+    /// - `boundary`: The closed shape enclosing the medium.
+    /// - `density`: The extinction coefficient per unit distance in the densest parts.
+    /// - `scale`: The frequency of the noise; larger values give smaller features.
+    /// - `tex`: The albedo of the medium.
+    /// - `phase`: The angular distribution of scattered light.
+    /// - `rng`: A random number generator used to build the noise.
+    ///
+    /// # Returns
+    /// A new `Hittables` instance containing the medium.
+    pub fn new_noise_medium(boundary: Arc<Hittables>, density: f64, scale: f64, tex: Arc<Textures>, phase: PhaseFunction, rng: &mut ThreadRng) -> Arc<Self> {
+        let field = DensityField::Noise { noise: Box::new(Perlin::new(rng)), scale };
+        Arc::new(Self::HeterogeneousMedium(HeterogeneousMedium::new(boundary, field, density, tex, phase)))
+    }
+
     /// Returns the bounding box of the hittable object.
     ///
     /// # Returns
@@ -77,12 +388,22 @@ impl Hittables {
     pub fn bounding_box(&self) -> &AABB {
         match self {
             Self::Sphere(obj) => obj.bounding_box(),
+            Self::Ellipsoid(obj) => obj.bounding_box(),
             Self::BVH(obj) => obj.bounding_box(),
             Self::List(obj) => obj.bounding_box(),
             Self::Quad(obj) => obj.bounding_box(),
+            Self::Triangle(obj) => obj.bounding_box(),
+            Self::Ellipse(obj) => obj.bounding_box(),
+            Self::Plane(obj) => obj.bounding_box(),
+            Self::Cylinder(obj) => obj.bounding_box(),
+            Self::Cone(obj) => obj.bounding_box(),
+            Self::Torus(obj) => obj.bounding_box(),
+            Self::Sdf(obj) => obj.bounding_box(),
             Self::Translate(obj) => obj.bounding_box(),
             Self::RotY(obj) => obj.bounding_box(),
+            Self::Csg(obj) => obj.bounding_box(),
             Self::Medium(obj) => obj.bounding_box(),
+            Self::HeterogeneousMedium(obj) => obj.bounding_box(),
         }
     }
 
@@ -98,12 +419,48 @@ impl Hittables {
     pub fn hit(&self, ray: &Ray, interval: Interval, rng:&mut ThreadRng ) -> Option<HitRecord> {
         match self {
             Self::Sphere(obj) => obj.hit(ray, interval),
+            Self::Ellipsoid(obj) => obj.hit(ray, interval),
             Self::BVH(obj) => obj.hit(ray, interval, rng),
             Self::List(obj) => obj.hit(ray, interval, rng),
             Self::Quad(obj) => obj.hit(ray, interval),
+            Self::Triangle(obj) => obj.hit(ray, interval),
+            Self::Ellipse(obj) => obj.hit(ray, interval),
+            Self::Plane(obj) => obj.hit(ray, interval),
+            Self::Cylinder(obj) => obj.hit(ray, interval),
+            Self::Cone(obj) => obj.hit(ray, interval),
+            Self::Torus(obj) => obj.hit(ray, interval),
+            Self::Sdf(obj) => obj.hit(ray, interval),
             Self::Translate(obj) => obj.hit(ray, interval, rng),
             Self::RotY(obj) => obj.hit(ray, interval, rng),
+            Self::Csg(obj) => obj.hit(ray, interval, rng),
             Self::Medium(obj) => obj.hit(ray, interval, rng),
+            Self::HeterogeneousMedium(obj) => obj.hit(ray, interval, rng),
+        }
+    }
+
+    /// Returns the fraction of light that travels along a ray without being blocked.
+    ///
+    /// Surfaces block the ray completely, while media only attenuate it, so shadow
+    /// rays can pass through fog and smoke.
+    ///
+    /// # Arguments
+    /// - `ray`: The ray to trace.
+    /// - `interval`: The valid interval for the ray parameter `t`.
+    ///
+    /// # Returns
+    /// The transmittance along the ray, in [0, 1] for surfaces and constant media,
+    /// and an unbiased estimate of it for heterogeneous media.
+    pub fn transmittance(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> f64 {
+        match self {
+            Self::BVH(obj) => obj.transmittance(ray, interval, rng),
+            Self::List(obj) => obj.transmittance(ray, interval, rng),
+            Self::Translate(obj) => obj.transmittance(ray, interval, rng),
+            Self::RotY(obj) => obj.transmittance(ray, interval, rng),
+            Self::Medium(obj) => obj.transmittance(ray, interval, rng),
+            Self::HeterogeneousMedium(obj) => obj.transmittance(ray, interval, rng),
+            _ => {
+                if self.hit(ray, interval, rng).is_some() { 0. } else { 1. }
+            }
         }
     }
 }
@@ -184,10 +541,85 @@ impl HittableList {
         self.add(Hittables::new_moving_sphere(start, end, radius, mat));
     }
 
+    /// Adds a static ellipsoid to the hittable list.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ellipsoid.
+    /// - `radii`: The radii along the `right`, `up` and `right x up` directions.
+    /// - `right`: The direction of the first radius.
+    /// - `up`: The direction of the second radius.
+    /// - `mat`: The material of the ellipsoid.
+    pub fn add_ellipsoid(&mut self, center: Point3, radii: Vec3, right: Vec3, up: Vec3, mat: Arc<Materials>) {
+        self.add(Hittables::new_static_ellipsoid(center, radii, right, up, mat));
+    }
+
+    /// Adds a moving ellipsoid to the hittable list.
+    ///
+    /// # Arguments
+    /// - `start`: The center of the ellipsoid at time 0.
+    /// - `end`: The center of the ellipsoid at time 1.
+    /// - `radii`: The radii along the `right`, `up` and `right x up` directions.
+    /// - `right`: The direction of the first radius.
+    /// - `up`: The direction of the second radius.
+    /// - `mat`: The material of the ellipsoid.
+    pub fn add_moving_ellipsoid(&mut self, start: Point3, end: Point3, radii: Vec3, right: Vec3, up: Vec3, mat: Arc<Materials>) {
+        self.add(Hittables::new_moving_ellipsoid(start, end, radii, right, up, mat));
+    }
+
     pub fn add_quad(&mut self, q : Point3, u : Vec3, v : Vec3, mat : Arc<Materials>) {
         self.add(Hittables::new_quad(q, u, v, mat))
     }
 
+    pub fn add_triangle(&mut self, a: Point3, b: Point3, c: Point3, mat: Arc<Materials>) {
+        self.add(Hittables::new_triangle(a, b, c, mat))
+    }
+
+    pub fn add_triangle_with_uvs(&mut self, a: Point3, b: Point3, c: Point3, uvs: [(f64, f64); 3], mat: Arc<Materials>) {
+        self.add(Hittables::new_triangle_with_uvs(a, b, c, uvs, mat))
+    }
+
+    pub fn add_smooth_triangle(&mut self, a: Point3, b: Point3, c: Point3, normals: [Vec3; 3], uvs: [(f64, f64); 3], mat: Arc<Materials>) {
+        self.add(Hittables::new_smooth_triangle(a, b, c, normals, uvs, mat))
+    }
+
+    pub fn add_ellipse(&mut self, center: Point3, u: Vec3, v: Vec3, mat: Arc<Materials>) {
+        self.add(Hittables::new_ellipse(center, u, v, mat))
+    }
+
+    pub fn add_disk(&mut self, center: Point3, normal: Vec3, radius: f64, mat: Arc<Materials>) {
+        self.add(Hittables::new_disk(center, normal, radius, mat))
+    }
+
+    pub fn add_plane(&mut self, point: Point3, normal: Vec3, mat: Arc<Materials>) {
+        self.add(Hittables::new_plane(point, normal, mat))
+    }
+
+    pub fn add_cylinder(&mut self, base: Point3, axis: Vec3, radius: f64, capped: bool, mat: Arc<Materials>) {
+        self.add(Hittables::new_cylinder(base, axis, radius, capped, mat))
+    }
+
+    pub fn add_cone(&mut self, base: Point3, axis: Vec3, radius: f64, capped: bool, mat: Arc<Materials>) {
+        self.add(Hittables::new_cone(base, axis, radius, capped, mat))
+    }
+
+    pub fn add_torus(&mut self, center: Point3, axis: Vec3, major_radius: f64, minor_radius: f64, mat: Arc<Materials>) {
+        self.add(Hittables::new_torus(center, axis, major_radius, minor_radius, mat))
+    }
+
+    pub fn add_sdf(&mut self, sdf: Sdf, mat: Arc<Materials>) {
+        self.add(Hittables::new_sdf(sdf, mat))
+    }
+
+    /// Adds the triangles of a mesh to the hittable list, so they go into the same BVH as everything else.
+    ///
+    /// # Arguments
+    /// - `mesh`: The mesh, already subdivided and displaced as wanted.
+    /// - `mat`: The material of the mesh.
+    /// - `smooth`: Whether to shade with normals interpolated across faces rather than flat ones.
+    pub fn add_mesh(&mut self, mesh: &Mesh, mat: Arc<Materials>, smooth: bool) {
+        self.append(&mut mesh.triangles(mat, smooth))
+    }
+
     pub fn create_box(a : Point3, b : Point3, mat : Arc<Materials>) -> Self {
         Quad::create_box(a, b, mat)
     }
@@ -196,14 +628,31 @@ impl HittableList {
         Hittables::BVH(Box::new(BVHNode::from_list(self)))
     }
 
-    // pub fn add_medium(&mut self, boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>) {
-    //     self.add(Hittables::new_medium(boundary, density, tex))
-    // } 
+    #[allow(dead_code)]
+    pub fn add_medium(&mut self, boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>) {
+        self.add(Hittables::new_medium(boundary, density, tex))
+    }
+
+    #[allow(dead_code)]
+    pub fn add_medium_with(&mut self, boundary: Arc<Hittables>, density: f64, tex: Arc<Textures>, phase: PhaseFunction) {
+        self.add(Hittables::new_medium_with(boundary, density, tex, phase))
+    }
 
     pub fn add_solid_medium(&mut self, boundary: Arc<Hittables>, density: f64, albedo: Color3) {
         self.add(Hittables::new_solid_medium(boundary, density, albedo))
     } 
 
+    #[allow(dead_code)]
+    pub fn add_grid_medium(&mut self, boundary: Arc<Hittables>, path: &str, density: f64, tex: Arc<Textures>, phase: PhaseFunction) -> Result<(), Error> {
+        self.add(Hittables::new_grid_medium(boundary, path, density, tex, phase)?);
+        Ok(())
+    }
+
+    pub fn add_noise_medium(&mut self, boundary: Arc<Hittables>, density: f64, scale: f64, tex: Arc<Textures>, phase: PhaseFunction, rng: &mut ThreadRng) {
+        self.add(Hittables::new_noise_medium(boundary, density, scale, tex, phase, rng))
+    }
+
+    #[allow(clippy::wrong_self_convention)]
     pub fn into_hittable(&mut self) -> Arc<Hittables> {
         Arc::new(Hittables::List(Box::new(self.clone())))
     }
@@ -216,15 +665,22 @@ impl HittableList {
         let mut final_hit_record = None;
         let mut closest_so_far = interval.max;
         for object in &self.objects {
-            match object.hit(ray, Interval::new(interval.min, closest_so_far), rng) {
-                Some(hr) => {
-                        closest_so_far = hr.t;
-                        final_hit_record = Some(hr);
-                        },
-                None => (),
-                
+            if let Some(hr) = object.hit(ray, Interval::new(interval.min, closest_so_far), rng) {
+                closest_so_far = hr.t;
+                final_hit_record = Some(hr);
+            }
+        }
+        final_hit_record
+    }
+
+    pub fn transmittance(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> f64 {
+        let mut transmittance = 1.;
+        for object in &self.objects {
+            transmittance *= object.transmittance(ray, interval, rng);
+            if transmittance == 0. {
+                break;
             }
         }
-        return final_hit_record;
+        transmittance
     }
 }
@@ -0,0 +1,531 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind};
+
+use crate::hittables::hittables::HittableList;
+use crate::prelude::*;
+
+/// How a mesh is refined into a smooth surface.
+///
+/// # Variants
+/// - `Loop`: Loop subdivision, which splits every triangle into four; polygons are triangulated first.
+/// - `CatmullClark`: Catmull-Clark subdivision, which turns every polygon into quads.
+#[derive(Debug, Clone, Copy)]
+pub enum Subdivision {
+    Loop,
+    CatmullClark,
+}
+
+/// A polygon mesh, such as a model imported from an OBJ file.
+///
+/// Meshes are refined and displaced as a whole, then tessellated into `Triangle`s that
+/// go into a `HittableList` and so into the BVH alongside every other object.
+///
+/// # Fields
+/// - `positions`: The position of each vertex.
+/// - `faces`: The vertex indices of each polygon, counter-clockwise around its front face.
+/// - `uvs`: The texture coordinates of each corner of each polygon, parallel to `faces`, so
+///   that a vertex on a UV seam can have different coordinates in the faces on either side.
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    pub positions: Vec<Point3>,
+    pub faces: Vec<Vec<usize>>,
+    pub uvs: Vec<Vec<(f64, f64)>>,
+}
+
+/// The connectivity of a mesh, which subdivision rules are written in terms of.
+struct Topology {
+    /// The faces on each side of every edge, keyed by its vertices in increasing order.
+    edge_faces: HashMap<(usize, usize), Vec<usize>>,
+    /// The faces around each vertex.
+    vertex_faces: Vec<Vec<usize>>,
+    /// The vertices sharing an edge with each vertex.
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(mesh: &Mesh) -> Self {
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        let mut vertex_faces = vec![Vec::new(); mesh.positions.len()];
+        let mut neighbors = vec![Vec::new(); mesh.positions.len()];
+
+        for (f, face) in mesh.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                let faces = edge_faces.entry(edge(a, b)).or_default();
+                if faces.is_empty() {
+                    neighbors[a].push(b);
+                    neighbors[b].push(a);
+                }
+                faces.push(f);
+                vertex_faces[a].push(f);
+            }
+        }
+
+        Self { edge_faces, vertex_faces, neighbors }
+    }
+
+    /// Whether an edge lies on the border of the mesh, with a face on one side only.
+    fn is_boundary(&self, a: usize, b: usize) -> bool {
+        self.edge_faces[&edge(a, b)].len() < 2
+    }
+
+    /// The neighbors of a vertex along the border of the mesh, which is empty for inner vertices.
+    fn boundary_neighbors(&self, v: usize) -> Vec<usize> {
+        self.neighbors[v].iter().copied().filter(|&n| self.is_boundary(v, n)).collect()
+    }
+}
+
+/// The key of the edge between two vertices.
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// The texture coordinates halfway between two others.
+fn midpoint(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (0.5 * (a.0 + b.0), 0.5 * (a.1 + b.1))
+}
+
+/// Splits the corners of a polygon into a fan of triangles around the first one.
+fn fan<T: Copy>(corners: &[T]) -> Vec<Vec<T>> {
+    (1..corners.len() - 1).map(|i| vec![corners[0], corners[i], corners[i + 1]]).collect()
+}
+
+/// The average of some points.
+fn average(points: impl Iterator<Item = Point3>) -> Point3 {
+    let (sum, count) = points.fold((Vec3::zero(), 0), |(sum, count), p| (sum + p, count + 1));
+    sum / count as f64
+}
+
+impl Mesh {
+    /// Creates a new mesh with all texture coordinates at zero.
+    ///
+    /// # Arguments
+    /// - `positions`: The position of each vertex.
+    /// - `faces`: The vertex indices of each polygon, counter-clockwise around its front face.
+    ///
+    /// # Returns
+    /// A new `Mesh`.
+    pub fn new(positions: Vec<Point3>, faces: Vec<Vec<usize>>) -> Self {
+        let uvs = faces.iter().map(|face| vec![(0., 0.); face.len()]).collect();
+        Self { positions, faces, uvs }
+    }
+
+    /// Loads a mesh from a Wavefront OBJ file.
+    ///
+    /// Only vertex positions (`v`), texture coordinates (`vt`) and faces (`f`) are read.
+    /// Face corners without texture coordinates get zero ones.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the OBJ file.
+    ///
+    /// # Returns
+    /// The loaded `Mesh`, or an error if the file cannot be read or is malformed.
+    #[allow(dead_code)]
+    pub fn load_obj(path: &str) -> Result<Self, Error> {
+        let mut positions = Vec::new();
+        let mut texture_coords = Vec::new();
+        let mut faces = Vec::new();
+        let mut uvs = Vec::new();
+
+        for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            let invalid = |message: &str| Error::new(ErrorKind::InvalidData, format!("{path}:{}: {message}", number + 1));
+            let mut words = line.split_whitespace();
+
+            match words.next() {
+                Some("v") => {
+                    let coords = parse_numbers(words).map_err(|_| invalid("invalid number"))?;
+                    let [x, y, z, ..] = coords[..] else {
+                        return Err(invalid("a vertex needs three coordinates"));
+                    };
+                    positions.push(Point3::new(x, y, z));
+                }
+                Some("vt") => {
+                    let coords = parse_numbers(words).map_err(|_| invalid("invalid number"))?;
+                    let [u, ref rest @ ..] = coords[..] else {
+                        return Err(invalid("a texture coordinate needs a value"));
+                    };
+                    texture_coords.push((u, rest.first().copied().unwrap_or(0.)));
+                }
+                Some("f") => {
+                    // Corners look like `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+                    let mut face = Vec::new();
+                    let mut face_uvs = Vec::new();
+                    for corner in words {
+                        let mut indices = corner.split('/');
+                        let v = indices.next().and_then(|s| resolve_index(s, positions.len()));
+                        let v = v.ok_or_else(|| invalid("invalid vertex index"))?;
+                        let uv = match indices.next().filter(|s| !s.is_empty()) {
+                            Some(s) => {
+                                let vt = resolve_index(s, texture_coords.len()).ok_or_else(|| invalid("invalid texture index"))?;
+                                texture_coords[vt]
+                            }
+                            None => (0., 0.),
+                        };
+                        if face.contains(&v) {
+                            return Err(invalid("a face cannot repeat a vertex"));
+                        }
+                        face.push(v);
+                        face_uvs.push(uv);
+                    }
+                    if face.len() < 3 {
+                        return Err(invalid("a face needs at least three vertices"));
+                    }
+                    faces.push(face);
+                    uvs.push(face_uvs);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { positions, faces, uvs })
+    }
+
+    /// Refines the mesh towards a smooth surface.
+    ///
+    /// Every level quadruples the number of faces; two or three levels are usually enough.
+    ///
+    /// # Arguments
+    /// - `scheme`: The subdivision rules to use.
+    /// - `levels`: How many times to subdivide.
+    ///
+    /// # Returns
+    /// The subdivided `Mesh`.
+    pub fn subdivide(&self, scheme: Subdivision, levels: usize) -> Self {
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = match scheme {
+                Subdivision::Loop => mesh.loop_step(),
+                Subdivision::CatmullClark => mesh.catmull_clark_step(),
+            };
+        }
+        mesh
+    }
+
+    /// Moves every vertex along its normal by the brightness of a texture.
+    ///
+    /// Displacement only moves vertices, so the mesh should be subdivided finely enough first.
+    /// A vertex on a UV seam is looked up at the coordinates of the first face it is a corner of.
+    ///
+    /// # Arguments
+    /// - `tex`: The height map, looked up at the texture coordinates and position of each vertex.
+    /// - `scale`: The distance a height of one moves a vertex by; negative values push inwards.
+    ///
+    /// # Returns
+    /// The displaced `Mesh`.
+    pub fn displace(&self, tex: &Textures, scale: f64) -> Self {
+        let normals = self.vertex_normals();
+        let uvs = self.vertex_uvs();
+        let mut mesh = self.clone();
+        for (v, position) in mesh.positions.iter_mut().enumerate() {
+            let (u, w) = uvs[v];
+            let height = tex.value(u, w, position).luminance();
+            *position = *position + scale * height * normals[v];
+        }
+        mesh
+    }
+
+    /// Tessellates the mesh into triangles, textured with the coordinates of their corners.
+    ///
+    /// # Arguments
+    /// - `mat`: The material of the mesh.
+    /// - `smooth`: Whether to shade with vertex normals interpolated across each triangle.
+    ///
+    /// # Returns
+    /// A `HittableList` of the triangles, skipping degenerate ones.
+    pub fn triangles(&self, mat: Arc<Materials>, smooth: bool) -> HittableList {
+        let normals = self.vertex_normals();
+        let mut list = HittableList::empty();
+        let mesh = self.triangulated();
+        for (face, uvs) in mesh.faces.iter().zip(&mesh.uvs) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.positions[face[i]]);
+            if (b - a).cross(&(c - a)).norm() == 0. {
+                continue;
+            }
+            let uvs = [uvs[0], uvs[1], uvs[2]];
+            if smooth {
+                list.add_smooth_triangle(a, b, c, [0, 1, 2].map(|i| normals[face[i]]), uvs, mat.clone());
+            } else {
+                list.add_triangle_with_uvs(a, b, c, uvs, mat.clone());
+            }
+        }
+        list
+    }
+
+    /// The area-weighted average normal of the faces around each vertex.
+    fn vertex_normals(&self) -> Vec<Vec3> {
+        let mut normals = vec![Vec3::zero(); self.positions.len()];
+        for face in &self.faces {
+            let origin = self.positions[face[0]];
+            let normal = face.windows(2).skip(1).fold(Vec3::zero(), |sum, pair| {
+                sum + (self.positions[pair[0]] - origin).cross(&(self.positions[pair[1]] - origin))
+            });
+            for &v in face {
+                normals[v] = normals[v] + normal;
+            }
+        }
+        normals
+            .into_iter()
+            .map(|n| if n.norm() > 0. { n.normalize() } else { n })
+            .collect()
+    }
+
+    /// The texture coordinates of each vertex, taken from the first face it is a corner of.
+    fn vertex_uvs(&self) -> Vec<(f64, f64)> {
+        let mut uvs = vec![None; self.positions.len()];
+        for (face, face_uvs) in self.faces.iter().zip(&self.uvs) {
+            for (&v, &uv) in face.iter().zip(face_uvs) {
+                uvs[v].get_or_insert(uv);
+            }
+        }
+        uvs.into_iter().map(|uv| uv.unwrap_or((0., 0.))).collect()
+    }
+
+    /// Splits every polygon into a fan of triangles around its first vertex.
+    fn triangulated(&self) -> Self {
+        Self {
+            positions: self.positions.clone(),
+            faces: self.faces.iter().flat_map(|face| fan(face)).collect(),
+            uvs: self.uvs.iter().flat_map(|uvs| fan(uvs)).collect(),
+        }
+    }
+
+    /// Splits every triangle into four, smoothing old vertices and placing new ones by the Loop rules.
+    fn loop_step(&self) -> Self {
+        let mesh = self.triangulated();
+        let topology = Topology::new(&mesh);
+        let p = &mesh.positions;
+
+        let mut positions: Vec<Point3> = (0..p.len())
+            .map(|v| match topology.boundary_neighbors(v)[..] {
+                [] => {
+                    let k = topology.neighbors[v].len() as f64;
+                    let beta = if k == 3. { 3. / 16. } else { 3. / (8. * k) };
+                    let sum = topology.neighbors[v].iter().fold(Vec3::zero(), |sum, &n| sum + p[n]);
+                    (1. - k * beta) * p[v] + beta * sum
+                }
+                [a, b] => 0.75 * p[v] + 0.125 * (p[a] + p[b]),
+                // Corners where the border meets itself are kept sharp.
+                _ => p[v],
+            })
+            .collect();
+
+        let mut edge_points = HashMap::new();
+        let mut edge_point = |a: usize, b: usize| {
+            *edge_points.entry(edge(a, b)).or_insert_with(|| {
+                let faces = &topology.edge_faces[&edge(a, b)];
+                let opposite = |f: usize| mesh.faces[f].iter().copied().find(|&v| v != a && v != b);
+                // Faces repeating a vertex have no opposite one, and are treated like a border.
+                let position = match faces[..] {
+                    [f, g] => match (opposite(f), opposite(g)) {
+                        (Some(c), Some(d)) => 0.375 * (p[a] + p[b]) + 0.125 * (p[c] + p[d]),
+                        _ => 0.5 * (p[a] + p[b]),
+                    },
+                    _ => 0.5 * (p[a] + p[b]),
+                };
+                positions.push(position);
+                positions.len() - 1
+            })
+        };
+
+        // Texture coordinates are interpolated linearly within each face, so UV seams stay put.
+        let mut faces = Vec::new();
+        let mut uvs = Vec::new();
+        for (face, face_uvs) in mesh.faces.iter().zip(&mesh.uvs) {
+            let [a, b, c] = [face[0], face[1], face[2]];
+            let (ab, bc, ca) = (edge_point(a, b), edge_point(b, c), edge_point(c, a));
+            faces.extend([vec![a, ab, ca], vec![ab, b, bc], vec![ca, bc, c], vec![ab, bc, ca]]);
+
+            let [uv_a, uv_b, uv_c] = [face_uvs[0], face_uvs[1], face_uvs[2]];
+            let (uv_ab, uv_bc, uv_ca) = (midpoint(uv_a, uv_b), midpoint(uv_b, uv_c), midpoint(uv_c, uv_a));
+            uvs.extend([
+                vec![uv_a, uv_ab, uv_ca],
+                vec![uv_ab, uv_b, uv_bc],
+                vec![uv_ca, uv_bc, uv_c],
+                vec![uv_ab, uv_bc, uv_ca],
+            ]);
+        }
+
+        Self { positions, faces, uvs }
+    }
+
+    /// Splits every polygon into quads around its center, placing vertices by the Catmull-Clark rules.
+    fn catmull_clark_step(&self) -> Self {
+        let topology = Topology::new(self);
+        let p = &self.positions;
+
+        let face_points: Vec<Point3> = self.faces.iter().map(|face| average(face.iter().map(|&v| p[v]))).collect();
+
+        let mut positions: Vec<Point3> = (0..p.len())
+            .map(|v| match topology.boundary_neighbors(v)[..] {
+                [] => {
+                    let n = topology.vertex_faces[v].len() as f64;
+                    let f = average(topology.vertex_faces[v].iter().map(|&f| face_points[f]));
+                    let r = average(topology.neighbors[v].iter().map(|&m| 0.5 * (p[v] + p[m])));
+                    (f + 2. * r + (n - 3.) * p[v]) / n
+                }
+                [a, b] => 0.75 * p[v] + 0.125 * (p[a] + p[b]),
+                _ => p[v],
+            })
+            .collect();
+
+        let first_face_point = positions.len();
+        positions.extend(&face_points);
+
+        let mut edge_points = HashMap::new();
+        let mut edge_point = |a: usize, b: usize| {
+            *edge_points.entry(edge(a, b)).or_insert_with(|| {
+                let position = match topology.edge_faces[&edge(a, b)][..] {
+                    [f, g] => 0.25 * (p[a] + p[b] + face_points[f] + face_points[g]),
+                    _ => 0.5 * (p[a] + p[b]),
+                };
+                positions.push(position);
+                positions.len() - 1
+            })
+        };
+
+        // Texture coordinates are interpolated linearly within each face, so UV seams stay put.
+        let mut faces = Vec::new();
+        let mut uvs = Vec::new();
+        for (f, (face, face_uvs)) in self.faces.iter().zip(&self.uvs).enumerate() {
+            let k = face.len();
+            let (u, v) = face_uvs.iter().fold((0., 0.), |(u, v), uv| (u + uv.0, v + uv.1));
+            let center_uv = (u / k as f64, v / k as f64);
+            for i in 0..k {
+                let (previous, current, next) = ((i + k - 1) % k, i, (i + 1) % k);
+                let (to_next, from_previous) = (edge_point(face[current], face[next]), edge_point(face[previous], face[current]));
+                faces.push(vec![face[current], to_next, first_face_point + f, from_previous]);
+                uvs.push(vec![
+                    face_uvs[current],
+                    midpoint(face_uvs[current], face_uvs[next]),
+                    center_uv,
+                    midpoint(face_uvs[previous], face_uvs[current]),
+                ]);
+            }
+        }
+
+        Self { positions, faces, uvs }
+    }
+}
+
+/// Parses every word as a number.
+fn parse_numbers(words: std::str::SplitWhitespace) -> Result<Vec<f64>, std::num::ParseFloatError> {
+    words.map(str::parse).collect()
+}
+
+/// Turns a 1-based OBJ index, or a negative one counting back from the end, into a 0-based index.
+fn resolve_index(word: &str, count: usize) -> Option<usize> {
+    let i: i64 = word.parse().ok()?;
+    let resolved = if i < 0 { count as i64 + i } else { i - 1 };
+    (0..count as i64).contains(&resolved).then_some(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tetrahedron() -> Mesh {
+        let positions = vec![
+            Point3::new(1., 1., 1.),
+            Point3::new(1., -1., -1.),
+            Point3::new(-1., 1., -1.),
+            Point3::new(-1., -1., 1.),
+        ];
+        Mesh::new(positions, vec![vec![0, 1, 2], vec![0, 3, 1], vec![0, 2, 3], vec![1, 3, 2]])
+    }
+
+    fn cube() -> Mesh {
+        let positions = (0..8)
+            .map(|i| Point3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64))
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1],
+            vec![4, 5, 7, 6],
+            vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3],
+            vec![0, 4, 6, 2],
+            vec![1, 3, 7, 5],
+        ];
+        Mesh::new(positions, faces)
+    }
+
+    /// Writes an OBJ file to the temporary directory and loads it.
+    fn load(name: &str, contents: &str) -> Result<Mesh, Error> {
+        let path = std::env::temp_dir().join(format!("mesh-test-{}-{name}.obj", std::process::id()));
+        std::fs::write(&path, contents)?;
+        let mesh = Mesh::load_obj(path.to_str().unwrap());
+        std::fs::remove_file(&path)?;
+        mesh
+    }
+
+    fn assert_invalid(name: &str, contents: &str, message: &str) {
+        let error = load(name, contents).expect_err("expected a parse error");
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains(message), "{error}");
+    }
+
+    #[test]
+    fn loop_quadruples_triangles() {
+        let once = tetrahedron().subdivide(Subdivision::Loop, 1);
+        assert_eq!(once.faces.len(), 16);
+        // Every edge gains a vertex.
+        assert_eq!(once.positions.len(), 4 + 6);
+        assert_eq!(tetrahedron().subdivide(Subdivision::Loop, 3).faces.len(), 4 * 64);
+        // Quads are split in two first.
+        assert_eq!(cube().subdivide(Subdivision::Loop, 1).faces.len(), 12 * 4);
+    }
+
+    #[test]
+    fn catmull_clark_makes_a_quad_per_corner() {
+        let once = cube().subdivide(Subdivision::CatmullClark, 1);
+        assert_eq!(once.faces.len(), 24);
+        assert!(once.faces.iter().all(|face| face.len() == 4));
+        // Every edge and every face gains a vertex.
+        assert_eq!(once.positions.len(), 8 + 12 + 6);
+        assert_eq!(cube().subdivide(Subdivision::CatmullClark, 2).faces.len(), 96);
+        assert_eq!(tetrahedron().subdivide(Subdivision::CatmullClark, 1).faces.len(), 12);
+    }
+
+    #[test]
+    fn subdivision_keeps_uvs_per_corner() {
+        for scheme in [Subdivision::Loop, Subdivision::CatmullClark] {
+            let mesh = cube().subdivide(scheme, 2);
+            assert_eq!(mesh.uvs.len(), mesh.faces.len());
+            assert!(mesh.faces.iter().zip(&mesh.uvs).all(|(face, uvs)| face.len() == uvs.len()));
+        }
+    }
+
+    #[test]
+    fn loop_tolerates_faces_repeating_a_vertex() {
+        let mesh = Mesh::new(tetrahedron().positions, vec![vec![0, 1, 2], vec![0, 0, 1]]);
+        assert_eq!(mesh.subdivide(Subdivision::Loop, 1).faces.len(), 8);
+    }
+
+    #[test]
+    fn obj_keeps_texture_coordinates_per_corner() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvt 0.5 0.5\nf 1/1 2/2 3/3\nf 2/4 4 -2/1\n";
+        let mesh = load("seam", obj).unwrap();
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2], vec![1, 3, 2]]);
+        // Vertex 2 has different coordinates in each face.
+        assert_eq!(mesh.uvs, vec![vec![(0., 0.), (1., 0.), (0., 1.)], vec![(0.5, 0.5), (0., 0.), (0., 0.)]]);
+    }
+
+    #[test]
+    fn obj_parse_errors() {
+        assert_invalid("short-vertex", "v 1 2\n", "a vertex needs three coordinates");
+        assert_invalid("bad-number", "v 1 two 3\n", "invalid number");
+        assert_invalid("empty-vt", "vt\n", "a texture coordinate needs a value");
+        assert_invalid("small-face", "v 0 0 0\nv 1 0 0\nf 1 2\n", "a face needs at least three vertices");
+        assert_invalid("bad-vertex", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n", "invalid vertex index");
+        assert_invalid("bad-texture", "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nf 1/1 2/2 3\n", "invalid texture index");
+        assert_invalid("repeated", "v 0 0 0\nv 1 0 0\nf 1 1 2\n", "a face cannot repeat a vertex");
+        // Errors point at the offending line.
+        assert_invalid("line", "v 0 0 0\n\nv 0 0\n", ":3: ");
+    }
+
+    #[test]
+    fn obj_missing_file() {
+        let error = Mesh::load_obj("/nonexistent/mesh.obj").expect_err("expected an error");
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+}
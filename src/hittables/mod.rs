@@ -1,9 +1,17 @@
 mod bvh;
 mod quad;
+mod planar;
+mod cylinder;
+mod torus;
+mod csg;
 mod sphere;
 mod translation;
 mod constant_medium;
+mod heterogeneous_medium;
+pub mod sdf;
+pub mod mesh;
 pub mod aabb;
 pub mod hit_record;
+#[allow(clippy::module_inception)]
 pub mod hittables;
 
@@ -0,0 +1,289 @@
+use crate::prelude::*;
+
+/// A plane spanned by two edge vectors from a corner, shared by all flat shapes.
+///
+/// A point on the plane is `q + alpha * u + beta * v`; each shape only decides which
+/// `(alpha, beta)` lie inside it.
+#[derive(Debug, Clone)]
+pub(super) struct PlanarFrame {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    /// `n / (n . n)` for the unnormalized normal `n = u x v`, used to find `alpha` and `beta`.
+    w: Vec3,
+    normal: Vec3,
+    d: f64,
+}
+
+impl PlanarFrame {
+    /// Creates the plane through `q` spanned by `u` and `v`; its normal is `u x v`.
+    pub(super) fn new(q: Point3, u: Vec3, v: Vec3) -> Self {
+        let n = u.cross(&v);
+        let normal = n.normalize();
+        let d = normal.dot(&q);
+        let w = n / n.dot(&n);
+
+        Self { q, u, v, w, normal, d }
+    }
+
+    /// Intersects a ray with the plane.
+    ///
+    /// # Returns
+    /// The ray parameter of the intersection and its `(alpha, beta)` coordinates along
+    /// `u` and `v`, or `None` if the ray is parallel to the plane or hits it outside `interval`.
+    pub(super) fn intersect(&self, ray: &Ray, interval: Interval) -> Option<(f64, f64, f64)> {
+        let denom = self.normal.dot(&ray.direction);
+        if denom.abs() < 1e-8 {return None}
+
+        let t = (self.d - self.normal.dot(&ray.origin)) / denom;
+        if !interval.surrounds(t) {return None}
+
+        let planar_space_intersection = ray.at(t) - self.q;
+        let alpha = self.w.dot(&planar_space_intersection.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar_space_intersection));
+        Some((t, alpha, beta))
+    }
+
+    /// Builds the hit record of an intersection found by `intersect`, with `u` and `v` as its tangents.
+    ///
+    /// The point is rebuilt from its plane coordinates `(alpha, beta)`, so it lies on the plane up
+    /// to the rounding of a few operations, however inaccurate `t` is.
+    pub(super) fn hit_record(&self, ray: &Ray, t: f64, (alpha, beta): (f64, f64), (u, v): (f64, f64), mat: &Arc<Materials>) -> HitRecord {
+        let along_u = alpha * self.u;
+        let along_v = beta * self.v;
+        let mut res = HitRecord {
+            point: self.q + along_u + along_v,
+            error: gamma(7) * (self.q.abs() + along_u.abs() + along_v.abs()),
+            normal: self.normal,
+            shading_normal: self.normal,
+            t,
+            mat: mat.clone(),
+            front_face: false,
+            u,
+            v,
+            dpdu: self.u,
+            dpdv: self.v,
+            duvdx: (0., 0.),
+            duvdy: (0., 0.),
+        };
+        res.set_face_normal(ray, &self.normal);
+        res
+    }
+}
+
+/// A flat triangle, optionally shaded smoothly by interpolating normals given at its corners.
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    frame: PlanarFrame,
+    /// The texture coordinates at the corners.
+    uvs: [(f64, f64); 3],
+    /// The tangents along the texture coordinates, which are constant across the triangle.
+    dpdu: Vec3,
+    dpdv: Vec3,
+    /// The outward shading normals at the corners, if the triangle is shaded smoothly.
+    normals: Option<[Vec3; 3]>,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Triangle {
+    /// Creates a new triangle.
+    ///
+    /// # Arguments
+    /// - `a`, `b`, `c`: The corners; the front face is the one they wind counter-clockwise around.
+    /// - `mat`: The material of the triangle.
+    ///
+    /// # Returns
+    /// A new `Triangle`, with the barycentric coordinates of `b` and `c` as its UVs.
+    pub fn new(a: Point3, b: Point3, c: Point3, mat: Arc<Materials>) -> Self {
+        Self::with_uvs(a, b, c, [(0., 0.), (1., 0.), (0., 1.)], mat)
+    }
+
+    /// Creates a new triangle with texture coordinates given at its corners, as in meshes.
+    ///
+    /// # Arguments
+    /// - `a`, `b`, `c`: The corners; the front face is the one they wind counter-clockwise around.
+    /// - `uvs`: The texture coordinates at `a`, `b` and `c`, interpolated across the triangle.
+    /// - `mat`: The material of the triangle.
+    ///
+    /// # Returns
+    /// A new `Triangle`, whose tangents follow the texture coordinates, or its edges where the
+    /// texture coordinates do not span an area.
+    pub fn with_uvs(a: Point3, b: Point3, c: Point3, uvs: [(f64, f64); 3], mat: Arc<Materials>) -> Self {
+        let mut bbox = AABB::from_boxes(&AABB::from_points(a, b), &AABB::from_points(a, c));
+        bbox.pad_to_minimums();
+
+        // Solve `b - a = du1 dpdu + dv1 dpdv` and `c - a = du2 dpdu + dv2 dpdv`.
+        let (e1, e2) = (b - a, c - a);
+        let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+        let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+        let det = du1 * dv2 - dv1 * du2;
+        let (dpdu, dpdv) = if det.abs() > 1e-12 {
+            ((dv2 * e1 - dv1 * e2) / det, (du1 * e2 - du2 * e1) / det)
+        } else {
+            (e1, e2)
+        };
+
+        Self {
+            frame: PlanarFrame::new(a, e1, e2),
+            uvs,
+            dpdu,
+            dpdv,
+            normals: None,
+            mat,
+            bbox,
+        }
+    }
+
+    /// Creates a new triangle shaded with normals interpolated from its corners, as in smooth meshes.
+    ///
+    /// # Arguments
+    /// - `a`, `b`, `c`: The corners; the front face is the one they wind counter-clockwise around.
+    /// - `normals`: The outward shading normals at `a`, `b` and `c`.
+    /// - `uvs`: The texture coordinates at `a`, `b` and `c`.
+    /// - `mat`: The material of the triangle.
+    ///
+    /// # Returns
+    /// A new `Triangle`, whose geometric normal is still that of its plane.
+    pub fn with_normals(a: Point3, b: Point3, c: Point3, normals: [Vec3; 3], uvs: [(f64, f64); 3], mat: Arc<Materials>) -> Self {
+        Self {
+            normals: Some(normals),
+            ..Self::with_uvs(a, b, c, uvs, mat)
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(ray, interval)?;
+        if alpha < 0. || beta < 0. || alpha + beta > 1. {return None}
+
+        let gamma = 1. - alpha - beta;
+        let [uv_a, uv_b, uv_c] = self.uvs;
+        let uv = (
+            gamma * uv_a.0 + alpha * uv_b.0 + beta * uv_c.0,
+            gamma * uv_a.1 + alpha * uv_b.1 + beta * uv_c.1,
+        );
+
+        let mut res = self.frame.hit_record(ray, t, (alpha, beta), uv, &self.mat);
+        res.dpdu = self.dpdu;
+        res.dpdv = self.dpdv;
+        if let Some([na, nb, nc]) = self.normals {
+            let normal = gamma * na + alpha * nb + beta * nc;
+            if normal.norm() > 0. {
+                res.set_shading_normal(&normal.normalize());
+            }
+        }
+        Some(res)
+    }
+}
+
+/// A flat ellipse, or a disk when its two axes are perpendicular and equally long.
+#[derive(Debug, Clone)]
+pub struct Ellipse {
+    frame: PlanarFrame,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Ellipse {
+    /// Creates a new ellipse.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ellipse.
+    /// - `u`: The first semi-axis, from the center to the edge.
+    /// - `v`: The second semi-axis; the front face is the side `u x v` points to.
+    /// - `mat`: The material of the ellipse.
+    ///
+    /// # Returns
+    /// A new `Ellipse`, whose UVs are the angle around the center, in [0, 1],
+    /// and the distance from the center relative to the edge.
+    pub fn new(center: Point3, u: Vec3, v: Vec3, mat: Arc<Materials>) -> Self {
+        // The extent of `center + cos(a) * u + sin(a) * v` along each axis.
+        let extent = Vec3::new(
+            (u.x * u.x + v.x * v.x).sqrt(),
+            (u.y * u.y + v.y * v.y).sqrt(),
+            (u.z * u.z + v.z * v.z).sqrt(),
+        );
+        let mut bbox = AABB::from_points(center - extent, center + extent);
+        bbox.pad_to_minimums();
+
+        Self {
+            frame: PlanarFrame::new(center, u, v),
+            mat,
+            bbox,
+        }
+    }
+
+    /// Creates a new disk.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the disk.
+    /// - `normal`: The direction the front face points to.
+    /// - `radius`: The radius of the disk.
+    /// - `mat`: The material of the disk.
+    ///
+    /// # Returns
+    /// A new `Ellipse` with two equal, perpendicular semi-axes.
+    pub fn disk(center: Point3, normal: Vec3, radius: f64, mat: Arc<Materials>) -> Self {
+        let basis = Onb::new(&normal);
+        Self::new(center, radius * basis.u, radius * basis.v, mat)
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(ray, interval)?;
+        let radius = (alpha * alpha + beta * beta).sqrt();
+        if radius > 1. {return None}
+
+        let angle = beta.atan2(alpha);
+        let mut res = self.frame.hit_record(ray, t, (alpha, beta), (angle / (2. * PI) + 0.5, radius), &self.mat);
+        // The UVs are polar coordinates, so the tangents turn around the center.
+        res.dpdu = 2. * PI * (alpha * self.frame.v - beta * self.frame.u);
+        res.dpdv = if radius > 0. { (alpha * self.frame.u + beta * self.frame.v) / radius } else { Vec3::zero() };
+        Some(res)
+    }
+}
+
+/// An infinite plane, e.g. for a ground or a backdrop.
+#[derive(Debug, Clone)]
+pub struct Plane {
+    frame: PlanarFrame,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Plane {
+    /// Creates a new infinite plane.
+    ///
+    /// # Arguments
+    /// - `point`: Any point on the plane.
+    /// - `normal`: The direction the front face points to.
+    /// - `mat`: The material of the plane.
+    ///
+    /// # Returns
+    /// A new `Plane`, whose UVs repeat every unit along two perpendicular directions in it.
+    pub fn new(point: Point3, normal: Vec3, mat: Arc<Materials>) -> Self {
+        let basis = Onb::new(&normal);
+        Self {
+            frame: PlanarFrame::new(point, basis.u, basis.v),
+            mat,
+            bbox: AABB::universe(),
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let (t, alpha, beta) = self.frame.intersect(ray, interval)?;
+
+        Some(self.frame.hit_record(ray, t, (alpha, beta), (alpha - alpha.floor(), beta - beta.floor()), &self.mat))
+    }
+}
@@ -0,0 +1,313 @@
+use crate::hittables::sphere::sphere_tangents;
+use crate::prelude::*;
+
+/// The most steps sphere tracing takes along one ray before giving up.
+const MAX_STEPS: usize = 512;
+/// The distance to the surface, relative to the size of the shape, that counts as a hit.
+const RELATIVE_EPSILON: f64 = 1e-5;
+
+/// A composable signed distance function: negative inside a shape, positive outside,
+/// and never larger than the true distance to the surface.
+///
+/// Primitives are centered on the origin; move them with `translate` and `scale`, and
+/// combine them with the boolean and blending operations.
+///
+/// # Variants
+/// - `Sphere`, `Box`, `RoundBox`, `Torus`, `Cylinder`: Primitives; tori and cylinders lie along `y`.
+/// - `Mandelbulb`: The power-`n` Mandelbulb fractal, within a radius of 2.
+/// - `Translate`, `Scale`: Move or uniformly resize a shape.
+/// - `Union`, `Intersection`, `Difference`: Boolean combinations; `Difference` cuts the second shape out of the first.
+/// - `SmoothUnion`: A union whose seam is blended over a distance `k`.
+/// - `Repeat`: Infinitely many copies of a shape, `period` apart along each axis with a non-zero period.
+#[derive(Debug, Clone)]
+pub enum Sdf {
+    Sphere { radius: f64 },
+    Box { half_extents: Vec3 },
+    RoundBox { half_extents: Vec3, radius: f64 },
+    Torus { major_radius: f64, minor_radius: f64 },
+    Cylinder { radius: f64, half_height: f64 },
+    Mandelbulb { power: f64, iterations: usize },
+    Translate { offset: Vec3, shape: Box<Sdf> },
+    Scale { factor: f64, shape: Box<Sdf> },
+    Union(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    Difference(Box<Sdf>, Box<Sdf>),
+    SmoothUnion { first: Box<Sdf>, second: Box<Sdf>, k: f64 },
+    Repeat { period: Vec3, shape: Box<Sdf> },
+}
+
+#[allow(dead_code)]
+impl Sdf {
+    /// A sphere of `radius`.
+    pub fn sphere(radius: f64) -> Self {
+        Self::Sphere { radius }
+    }
+
+    /// A box reaching `half_extents` from its center along each axis.
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::Box { half_extents }
+    }
+
+    /// A box reaching `half_extents` from its center, with edges rounded by `radius`.
+    pub fn round_box(half_extents: Vec3, radius: f64) -> Self {
+        Self::RoundBox { half_extents, radius }
+    }
+
+    /// A torus around the `y` axis.
+    pub fn torus(major_radius: f64, minor_radius: f64) -> Self {
+        Self::Torus { major_radius, minor_radius }
+    }
+
+    /// A capped cylinder along the `y` axis.
+    pub fn cylinder(radius: f64, half_height: f64) -> Self {
+        Self::Cylinder { radius, half_height }
+    }
+
+    /// The Mandelbulb fractal; `power` 8 gives the classic shape.
+    pub fn mandelbulb(power: f64, iterations: usize) -> Self {
+        Self::Mandelbulb { power, iterations }
+    }
+
+    /// Moves the shape by `offset`.
+    pub fn translate(self, offset: Vec3) -> Self {
+        Self::Translate { offset, shape: Box::new(self) }
+    }
+
+    /// Resizes the shape by `factor` around the origin.
+    pub fn scale(self, factor: f64) -> Self {
+        Self::Scale { factor, shape: Box::new(self) }
+    }
+
+    /// The space inside either shape.
+    pub fn union(self, other: Sdf) -> Self {
+        Self::Union(Box::new(self), Box::new(other))
+    }
+
+    /// The space inside both shapes.
+    pub fn intersection(self, other: Sdf) -> Self {
+        Self::Intersection(Box::new(self), Box::new(other))
+    }
+
+    /// The space inside this shape but not `other`.
+    pub fn difference(self, other: Sdf) -> Self {
+        Self::Difference(Box::new(self), Box::new(other))
+    }
+
+    /// The union of both shapes, with the seam blended over a distance `k`.
+    pub fn smooth_union(self, other: Sdf, k: f64) -> Self {
+        Self::SmoothUnion { first: Box::new(self), second: Box::new(other), k }
+    }
+
+    /// Copies the shape infinitely, `period` apart; a zero component disables repetition
+    /// along that axis. Intersect the result with a finite shape to bound it.
+    pub fn repeat(self, period: Vec3) -> Self {
+        Self::Repeat { period, shape: Box::new(self) }
+    }
+
+    /// Evaluates the signed distance from `p` to the surface.
+    pub fn distance(&self, p: &Point3) -> f64 {
+        match self {
+            Self::Sphere { radius } => p.length() - radius,
+            Self::Box { half_extents } => box_distance(p, half_extents),
+            Self::RoundBox { half_extents, radius } => {
+                let inner = *half_extents - Vec3::new(*radius, *radius, *radius);
+                box_distance(p, &inner) - radius
+            }
+            Self::Torus { major_radius, minor_radius } => {
+                let ring = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+                (ring * ring + p.y * p.y).sqrt() - minor_radius
+            }
+            Self::Cylinder { radius, half_height } => {
+                let dx = (p.x * p.x + p.z * p.z).sqrt() - radius;
+                let dy = p.y.abs() - half_height;
+                dx.max(dy).min(0.) + (dx.max(0.).powi(2) + dy.max(0.).powi(2)).sqrt()
+            }
+            Self::Mandelbulb { power, iterations } => mandelbulb_distance(p, *power, *iterations),
+            Self::Translate { offset, shape } => shape.distance(&(*p - *offset)),
+            Self::Scale { factor, shape } => shape.distance(&(*p / *factor)) * factor,
+            Self::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Self::Intersection(a, b) => a.distance(p).max(b.distance(p)),
+            Self::Difference(a, b) => a.distance(p).max(-b.distance(p)),
+            Self::SmoothUnion { first, second, k } => {
+                let (a, b) = (first.distance(p), second.distance(p));
+                let h = (0.5 + 0.5 * (b - a) / k).clamp(0., 1.);
+                b + (a - b) * h - k * h * (1. - h)
+            }
+            Self::Repeat { period, shape } => {
+                let mut q = *p;
+                for axis in 0..3 {
+                    if period[axis] != 0. {
+                        q[axis] -= period[axis] * (p[axis] / period[axis]).round();
+                    }
+                }
+                shape.distance(&q)
+            }
+        }
+    }
+
+    /// Returns a box enclosing the shape, which is infinite for unbounded shapes.
+    pub fn bounds(&self) -> AABB {
+        let cube = |r: f64| AABB::from_points(Point3::new(-r, -r, -r), Point3::new(r, r, r));
+        match self {
+            Self::Sphere { radius } => cube(*radius),
+            Self::Box { half_extents } | Self::RoundBox { half_extents, .. } => {
+                AABB::from_points(-1. * *half_extents, *half_extents)
+            }
+            Self::Torus { major_radius, minor_radius } => {
+                let r = major_radius + minor_radius;
+                AABB::from_points(Point3::new(-r, -minor_radius, -r), Point3::new(r, *minor_radius, r))
+            }
+            Self::Cylinder { radius, half_height } => {
+                AABB::from_points(Point3::new(-radius, -half_height, -radius), Point3::new(*radius, *half_height, *radius))
+            }
+            Self::Mandelbulb { .. } => cube(2.),
+            Self::Translate { offset, shape } => shape.bounds().offset(*offset),
+            Self::Scale { factor, shape } => {
+                let b = shape.bounds();
+                let axis = |n: i32| b.axis_interval(n);
+                AABB::from_points(
+                    *factor * Point3::new(axis(0).min, axis(1).min, axis(2).min),
+                    *factor * Point3::new(axis(0).max, axis(1).max, axis(2).max),
+                )
+            }
+            Self::Union(a, b) => AABB::from_boxes(&a.bounds(), &b.bounds()),
+            Self::Intersection(a, b) => AABB::overlap(&a.bounds(), &b.bounds()),
+            Self::Difference(a, _) => a.bounds(),
+            Self::SmoothUnion { first, second, k } => {
+                let b = AABB::from_boxes(&first.bounds(), &second.bounds());
+                let grow = |n: i32| {
+                    let mut interval = b.axis_interval(n);
+                    interval.expand(2. * k);
+                    interval
+                };
+                AABB::new(grow(0), grow(1), grow(2))
+            }
+            Self::Repeat { .. } => AABB::universe(),
+        }
+    }
+}
+
+/// The signed distance to a box centered on the origin.
+fn box_distance(p: &Point3, half_extents: &Vec3) -> f64 {
+    let q = Vec3::new(p.x.abs(), p.y.abs(), p.z.abs()) - *half_extents;
+    let outside = Vec3::new(q.x.max(0.), q.y.max(0.), q.z.max(0.)).length();
+    outside + q.x.max(q.y).max(q.z).min(0.)
+}
+
+/// A distance estimate for the Mandelbulb, from the running derivative of its iteration.
+fn mandelbulb_distance(p: &Point3, power: f64, iterations: usize) -> f64 {
+    let mut z = *p;
+    let mut dr = 1.;
+    let mut r = z.length();
+    for _ in 0..iterations {
+        if r > 2. {
+            break;
+        }
+        let theta = (z.z / r.max(1e-12)).clamp(-1., 1.).acos() * power;
+        let phi = z.y.atan2(z.x) * power;
+        dr = r.powf(power - 1.) * power * dr + 1.;
+
+        let zr = r.powf(power);
+        z = zr * Vec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()) + *p;
+        r = z.length();
+    }
+    0.5 * r.max(1e-12).ln() * r / dr
+}
+
+/// A shape defined by a signed distance function, rendered by sphere tracing.
+///
+/// Rays are marched through the bounding box in steps equal to the distance to the
+/// surface, which can never overshoot it, until they get within a small fraction of the
+/// size of the shape.
+#[derive(Debug, Clone)]
+pub struct SdfSurface {
+    sdf: Sdf,
+    mat: Arc<Materials>,
+    bbox: AABB,
+    /// The distance that counts as touching the surface.
+    epsilon: f64,
+}
+
+impl SdfSurface {
+    /// Creates a new SDF surface.
+    ///
+    /// # Arguments
+    /// - `sdf`: The distance function; it must be bounded, e.g. by intersecting repetitions with a box.
+    /// - `mat`: The material of the surface.
+    ///
+    /// # Returns
+    /// A new `SdfSurface`.
+    pub fn new(sdf: Sdf, mat: Arc<Materials>) -> Self {
+        let mut bbox = sdf.bounds();
+        bbox.pad_to_minimums();
+
+        let size = (0..3)
+            .map(|axis| bbox.axis_interval(axis).size().powi(2))
+            .sum::<f64>()
+            .sqrt();
+        let epsilon = if size.is_finite() { RELATIVE_EPSILON * size } else { RELATIVE_EPSILON };
+
+        Self { sdf, mat, bbox, epsilon }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let inside_box = self.bbox.hit(ray)?;
+        let length = ray.direction.length();
+        let mut t = interval.min.max(inside_box.min);
+        let end = interval.max.min(inside_box.max);
+
+        // Rays spawned from the surface start at least `epsilon` off it, so only a ray starting
+        // right on the surface for another reason needs stepping off it.
+        for _ in 0..MAX_STEPS {
+            if t > end {
+                return None;
+            }
+            let distance = self.sdf.distance(&ray.at(t)).abs();
+            if distance < self.epsilon && t > interval.min {
+                return Some(self.hit_record(ray, t));
+            }
+            t += distance.max(self.epsilon) / length;
+        }
+        None
+    }
+
+    /// Builds the hit record at `t`, with the normal from the gradient of the distance.
+    fn hit_record(&self, ray: &Ray, t: f64) -> HitRecord {
+        let point = ray.at(t);
+        let h = self.epsilon;
+        let sample = |offset: Vec3| self.sdf.distance(&(point + offset)) * offset;
+        let gradient = sample(Vec3::new(h, -h, -h))
+            + sample(Vec3::new(-h, -h, h))
+            + sample(Vec3::new(-h, h, -h))
+            + sample(Vec3::new(h, h, h));
+        let normal = gradient.normalize();
+
+        let u = (-normal.z).atan2(normal.x) / (2. * PI) + 0.5;
+        let v = (-normal.y).acos() / PI;
+        let (dpdu, dpdv) = sphere_tangents(&normal, 1.);
+        // Sphere tracing stops anywhere within `epsilon` of the surface; twice that puts rays
+        // spawned from the point at least `epsilon` from the surface on either side.
+        let error = 2. * self.epsilon * Vec3::new(1., 1., 1.) + gamma(1) * point.abs();
+        let mut res = HitRecord {
+            point,
+            error,
+            normal,
+            shading_normal: normal,
+            t,
+            mat: self.mat.clone(),
+            front_face: false,
+            u,
+            v,
+            dpdu,
+            dpdv,
+            duvdx: (0., 0.),
+            duvdy: (0., 0.),
+        };
+        res.set_face_normal(ray, &normal);
+        res
+    }
+}
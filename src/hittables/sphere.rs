@@ -99,29 +99,218 @@ impl Sphere {
         }
 
         //Find nearest root that lies in range
-        let sqrtd = discriminant.sqrt();
-        let mut root = (h - sqrtd) / a;
-        if !interval.surrounds(root) {
-            root = (h + sqrtd) / a;
-            if !interval.surrounds(root) {
-                return None;
-            }
-        }
+        let t = nearest_root(a, h, c, discriminant, interval)?;
 
-        let t = root;
-        let point = ray.at(root);
-        let normal = (point - center) / self.radius;
+        // Project the point back onto the sphere, which bounds its error much more tightly
+        // than the error of `t` would.
+        let mut offset = ray.at(t) - center;
+        offset = offset * (self.radius / offset.length());
+        let point = center + offset;
+        let error = gamma(5) * offset.abs() + gamma(1) * point.abs();
+        let normal = offset / self.radius;
 
         let (u, v) = Self::get_sphere_uv(&normal);
+        let (dpdu, dpdv) = sphere_tangents(&normal, self.radius);
 
         let mut res = HitRecord {
             point,
+            error,
             normal,
+            shading_normal: normal,
             t,
             mat: Arc::clone(&self.mat),
             front_face: false,
             u,
             v,
+            dpdu,
+            dpdv,
+            duvdx: (0., 0.),
+            duvdy: (0., 0.),
+        };
+        res.set_face_normal(ray, &normal);
+        Some(res)
+    }
+}
+
+/// Finds both roots of `a t^2 - 2 h t + c`, where `discriminant = h^2 - a c` is not negative.
+///
+/// The roots are computed as `c / q` and `q / a` with `q = h + sign(h) sqrt(discriminant)`,
+/// which avoids the cancellation that loses the accuracy of the small root of rays starting
+/// on the surface.
+///
+/// # Returns
+/// The roots in increasing order.
+pub(super) fn quadratic_roots(a: f64, h: f64, c: f64, discriminant: f64) -> [f64; 2] {
+    let q = h + h.signum() * discriminant.sqrt();
+    if q == 0. {
+        return [0., 0.];
+    }
+    let (near, far) = (c / q, q / a);
+    if near <= far { [near, far] } else { [far, near] }
+}
+
+/// Finds the nearest root of `a t^2 - 2 h t + c` strictly inside an interval, as `quadratic_roots` does.
+pub(super) fn nearest_root(a: f64, h: f64, c: f64, discriminant: f64, interval: Interval) -> Option<f64> {
+    quadratic_roots(a, h, c, discriminant).into_iter().find(|&t| interval.surrounds(t))
+}
+
+/// The derivatives of a point on a sphere with respect to the UVs of `Sphere::get_sphere_uv`.
+///
+/// # Arguments
+/// - `n`: The unit vector from the center to the point.
+/// - `radius`: The radius of the sphere.
+///
+/// # Returns
+/// `(dpdu, dpdv)`, which are zero at the poles, where the mapping is singular.
+pub(super) fn sphere_tangents(n: &Vec3, radius: f64) -> (Vec3, Vec3) {
+    let s = (n.x * n.x + n.z * n.z).sqrt();
+    if s < 1e-12 {
+        return (Vec3::zero(), Vec3::zero());
+    }
+    let dpdu = 2. * PI * radius * Vec3::new(n.z, 0., -n.x);
+    let dpdv = PI * radius * Vec3::new(-n.x * n.y / s, s, -n.y * n.z / s);
+    (dpdu, dpdv)
+}
+
+/// Represents an ellipsoid, i.e. a sphere scaled by a different radius along each of three
+/// perpendicular axes, that can be static or moving in the scene.
+///
+/// Rays are intersected with the unit sphere in the ellipsoid's local frame, where the
+/// radii are scaled away, and normals are mapped back with the inverse scaling.
+///
+/// # Fields
+/// - `center`: The center of the ellipsoid, represented as a `Ray`.
+/// - `basis`: The directions of the three radii.
+/// - `radii`: The radius along each axis of `basis`.
+/// - `mat`: The material of the ellipsoid.
+/// - `bbox`: The bounding box of the ellipsoid.
+#[derive(Debug, Clone)]
+pub struct Ellipsoid {
+    center: Ray,
+    basis: Onb,
+    radii: Vec3,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Ellipsoid {
+    /// Creates a new static ellipsoid.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ellipsoid.
+    /// - `radii`: The radii along the `right`, `up` and `right x up` directions.
+    /// - `right`: The direction of the first radius.
+    /// - `up`: The direction of the second radius; only its part perpendicular to `right` is used.
+    /// - `mat`: The material of the ellipsoid.
+    ///
+    /// # Returns
+    /// A new `Ellipsoid`, with UVs laid out like those of a `Sphere` in its own frame.
+    pub fn new_static(center: Point3, radii: Vec3, right: Vec3, up: Vec3, mat: Arc<Materials>) -> Self {
+        Self::new_moving(center, center, radii, right, up, mat)
+    }
+
+    /// Creates a new moving ellipsoid.
+    ///
+    /// # Arguments
+    /// - `center_start`: The center of the ellipsoid at time 0.
+    /// - `center_end`: The center of the ellipsoid at time 1.
+    /// - `radii`: The radii along the `right`, `up` and `right x up` directions.
+    /// - `right`: The direction of the first radius.
+    /// - `up`: The direction of the second radius; only its part perpendicular to `right` is used.
+    /// - `mat`: The material of the ellipsoid.
+    ///
+    /// If `right` is zero it is taken to be the x axis, and if `up` is zero or parallel to
+    /// `right` any direction perpendicular to `right` is used instead.
+    ///
+    /// # Returns
+    /// A new `Ellipsoid`.
+    pub fn new_moving(
+        center_start: Point3,
+        center_end: Point3,
+        radii: Vec3,
+        right: Vec3,
+        up: Vec3,
+        mat: Arc<Materials>,
+    ) -> Self {
+        // Degenerate directions fall back to an arbitrary frame rather than a NaN one.
+        let u = if right.length() > 0. { right.normalize() } else { Vec3::new(1., 0., 0.) };
+        let v = up - up.dot(&u) * u;
+        let v = if v.length() > 1e-8 * up.length() { v.normalize() } else { Onb::new(&u).u };
+        let basis = Onb { u, v, w: u.cross(&v) };
+        let center = Ray::new(center_start, center_end - center_start);
+
+        // The extent of the ellipsoid along world axis `i` is the length of the `i`-th
+        // components of its three semi-axes.
+        let semi_axes = [radii.x * basis.u, radii.y * basis.v, radii.z * basis.w];
+        let extent = |i: usize| semi_axes.iter().map(|a| a[i] * a[i]).sum::<f64>().sqrt();
+        let rvec = Vec3::new(extent(0), extent(1), extent(2));
+        let box1 = AABB::from_points(center.at(0.) - rvec, center.at(0.) + rvec);
+        let box2 = AABB::from_points(center.at(1.) - rvec, center.at(1.) + rvec);
+
+        Self {
+            center,
+            basis,
+            radii,
+            mat,
+            bbox: AABB::from_boxes(&box1, &box2),
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    /// Determines if a ray hits the ellipsoid.
+    ///
+    /// # Arguments
+    /// - `ray`: The ray to test for intersection.
+    /// - `interval`: The valid interval for the ray parameter `t`.
+    ///
+    /// # Returns
+    /// An `Option<HitRecord>` containing the hit information if the ray intersects the ellipsoid,
+    /// or `None` if there is no intersection.
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        // Scaling the local frame by the inverse radii turns the ellipsoid into a unit sphere,
+        // without changing the ray parameter.
+        let center = self.center.at(ray.time);
+        let inv_radii = Vec3::new(1. / self.radii.x, 1. / self.radii.y, 1. / self.radii.z);
+        let origin = self.basis.inverse_transform(&(ray.origin - center)) * inv_radii;
+        let direction = self.basis.inverse_transform(&ray.direction) * inv_radii;
+
+        let a = direction.norm();
+        let h = -direction.dot(&origin);
+        let c = origin.norm() - 1.;
+        let discriminant = h * h - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = nearest_root(a, h, c, discriminant, interval)?;
+
+        // Project the point back onto the unit sphere, then bound the error of scaling and
+        // rotating it into place, and the origins of later rays back into the local frame.
+        let local = (origin + root * direction).normalize();
+        let normal = self.basis.transform(&(local * inv_radii)).normalize();
+        let (u, v) = Sphere::get_sphere_uv(&local);
+        let (dpdu, dpdv) = sphere_tangents(&local, 1.);
+        let to_world = |d: Vec3| self.basis.transform(&(d * self.radii));
+        let point = center + to_world(local);
+        let error = gamma(13) * self.basis.transform_abs(&(local * self.radii).abs()) + gamma(2) * point.abs();
+
+        let mut res = HitRecord {
+            point,
+            error,
+            normal,
+            shading_normal: normal,
+            t: root,
+            mat: Arc::clone(&self.mat),
+            front_face: false,
+            u,
+            v,
+            dpdu: to_world(dpdu),
+            dpdv: to_world(dpdv),
+            duvdx: (0., 0.),
+            duvdy: (0., 0.),
         };
         res.set_face_normal(ray, &normal);
         Some(res)
@@ -0,0 +1,158 @@
+use crate::hittables::cylinder::{angle_tangent, angle_uv, disk_extent, local_hit_record, LocalHit};
+use crate::prelude::*;
+
+/// A torus, i.e. a ring-shaped tube swept around an arbitrary axis.
+///
+/// Intersections are the roots of a quartic, found in a local frame where the axis runs
+/// along `z` and the ray is restarted close to the torus to keep them accurate.
+#[derive(Debug, Clone)]
+pub struct Torus {
+    center: Point3,
+    basis: Onb,
+    /// The distance from the center to the middle of the tube.
+    major_radius: f64,
+    /// The radius of the tube.
+    minor_radius: f64,
+    mat: Arc<Materials>,
+    bbox: AABB,
+}
+
+impl Torus {
+    /// Creates a new torus.
+    ///
+    /// # Arguments
+    /// - `center`: The center of the ring.
+    /// - `axis`: The direction the ring is swept around.
+    /// - `major_radius`: The distance from the center to the middle of the tube.
+    /// - `minor_radius`: The radius of the tube.
+    /// - `mat`: The material of the torus.
+    ///
+    /// # Returns
+    /// A new `Torus`, whose UVs are the angle around the axis and the angle around the tube, in [0, 1].
+    pub fn new(center: Point3, axis: Vec3, major_radius: f64, minor_radius: f64, mat: Arc<Materials>) -> Self {
+        let tube = Vec3::new(minor_radius, minor_radius, minor_radius);
+        let extent = disk_extent(&axis, major_radius) + tube;
+        let mut bbox = AABB::from_points(center - extent, center + extent);
+        bbox.pad_to_minimums();
+
+        Self {
+            center,
+            basis: Onb::new(&axis),
+            major_radius,
+            minor_radius,
+            mat,
+            bbox,
+        }
+    }
+
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bbox
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let length = ray.direction.length();
+        let origin = self.basis.inverse_transform(&(ray.origin - self.center));
+        let direction = self.basis.inverse_transform(&ray.direction) / length;
+
+        // Restart the ray where it enters the bounding sphere; in distances along the
+        // normalized direction, which are `length` times the ray parameter.
+        let bound = self.major_radius + self.minor_radius;
+        let h = -origin.dot(&direction);
+        let discriminant = h * h - (origin.norm() - bound * bound);
+        if discriminant < 0. {
+            return None;
+        }
+        let sqrtd = discriminant.sqrt();
+        let start = (h - sqrtd).max(interval.min * length);
+        let end = (h + sqrtd).min(interval.max * length);
+        if start >= end {
+            return None;
+        }
+        let o = origin + start * direction;
+        let d = direction;
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2), expanded in the distance from `o`.
+        let r2 = self.major_radius * self.major_radius;
+        let k = 2. * o.dot(&d);
+        let l = o.norm() + r2 - self.minor_radius * self.minor_radius;
+        let coefficients = [
+            l * l - 4. * r2 * (o.x * o.x + o.y * o.y),
+            2. * k * l - 8. * r2 * (o.x * d.x + o.y * d.y),
+            2. * l + k * k - 4. * r2 * (d.x * d.x + d.y * d.y),
+            2. * k,
+            1.,
+        ];
+
+        let s = polynomial_roots(&coefficients, 0., end - start)
+            .into_iter()
+            .find(|&s| interval.surrounds((start + s) / length))?;
+        let t = (start + s) / length;
+
+        // The root is only as accurate as the bisection, so project the point back onto the
+        // tube around the nearest point of the ring.
+        let ring = Vec3::new(o.x + s * d.x, o.y + s * d.y, 0.).normalize() * self.major_radius;
+        let normal = (o + s * d - ring).normalize();
+        let p = ring + self.minor_radius * normal;
+        let distance = (p.x * p.x + p.y * p.y).sqrt();
+        let tube_angle = (p.z.atan2(distance - self.major_radius) + PI) / (2. * PI);
+        let around_tube = 2. * PI * Vec3::new(-p.z * p.x / distance, -p.z * p.y / distance, distance - self.major_radius);
+
+        let hit = LocalHit {
+            t,
+            point: p,
+            error: gamma(6) * (ring.abs() + (self.minor_radius * normal).abs()),
+            normal,
+            uv: (angle_uv(&p), tube_angle),
+            tangents: (angle_tangent(&p), around_tube),
+        };
+        Some(local_hit_record(ray, hit, (&self.center, &self.basis), &self.mat))
+    }
+}
+
+/// Finds the real roots of a polynomial within an interval, in increasing order.
+///
+/// The roots of the derivative split the interval into pieces where the polynomial is
+/// monotonic, and each piece holds at most one root, which is found by bisection.
+///
+/// # Arguments
+/// - `coefficients`: The coefficients, starting with the constant term.
+/// - `lo`, `hi`: The interval to search.
+///
+/// # Returns
+/// The roots in `[lo, hi]`.
+fn polynomial_roots(coefficients: &[f64], lo: f64, hi: f64) -> Vec<f64> {
+    let degree = coefficients.len() - 1;
+    if degree == 1 {
+        let root = -coefficients[0] / coefficients[1];
+        return if (lo..=hi).contains(&root) { vec![root] } else { vec![] };
+    }
+
+    let derivative: Vec<f64> = (1..=degree).map(|i| i as f64 * coefficients[i]).collect();
+    let mut bounds = vec![lo];
+    bounds.extend(polynomial_roots(&derivative, lo, hi));
+    bounds.push(hi);
+
+    let eval = |x: f64| coefficients.iter().rev().fold(0., |acc, c| acc * x + c);
+    let mut roots = Vec::new();
+    for piece in bounds.windows(2) {
+        let (mut a, mut b) = (piece[0], piece[1]);
+        let (fa, fb) = (eval(a), eval(b));
+        if fa == 0. {
+            roots.push(a);
+            continue;
+        }
+        if fa.signum() == fb.signum() {
+            continue;
+        }
+        for _ in 0..64 {
+            let mid = 0.5 * (a + b);
+            if eval(mid).signum() == fa.signum() {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        roots.push(0.5 * (a + b));
+    }
+    roots
+}
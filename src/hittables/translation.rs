@@ -21,12 +21,25 @@ impl Translate {
         &self.bbox
     }
 
+    /// Moves a ray from world space into the space of the wrapped object.
+    fn to_object(&self, ray: &Ray) -> Ray {
+        let mut offset_ray = *ray;
+        offset_ray.origin = ray.origin - self.offset;
+        offset_ray
+    }
+
+    pub fn transmittance(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> f64 {
+        self.object.transmittance(&self.to_object(ray), interval, rng)
+    }
+
     pub fn hit(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng ) -> Option<HitRecord> {
-        let offest_ray = Ray::new_time(ray.origin - self.offset, ray.direction, ray.time);
+        let offest_ray = self.to_object(ray);
 
         match self.object.hit(&offest_ray, interval, rng)  {
             Some(mut hr) => {
                 hr.point = hr.point + self.offset;
+                // Both moving the point and moving the next ray back round once.
+                hr.error = hr.error + gamma(2) * hr.point.abs();
                 Some(hr)
             },
             None => None
@@ -86,7 +99,8 @@ impl RotateY {
         &self.bbox
     }
 
-    pub fn hit(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> Option<HitRecord> {
+    /// Rotates a ray from world space into the space of the wrapped object.
+    fn to_object(&self, ray: &Ray) -> Ray {
         let origin = Point3::new(
             self.cos_theta * ray.origin.x - self.sin_theta * ray.origin.z,
             ray.origin.y,
@@ -99,21 +113,43 @@ impl RotateY {
             self.sin_theta * ray.direction.x + self.cos_theta * ray.direction.z,  
         );
 
-        let new_ray = Ray::new_time(origin, direction, ray.time);
+        let mut new_ray = *ray;
+        new_ray.origin = origin;
+        new_ray.direction = direction;
+        new_ray
+    }
+
+    /// Rotates a point or vector from the space of the wrapped object back into world space.
+    fn to_world(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * v.x + self.sin_theta * v.z,
+            v.y,
+            -self.sin_theta * v.x + self.cos_theta * v.z,
+        )
+    }
+
+    /// Rotates an error bound into world space, so that it bounds the rotated error whatever its signs.
+    fn to_world_abs(&self, v: &Vec3) -> Vec3 {
+        let (cos, sin) = (self.cos_theta.abs(), self.sin_theta.abs());
+        Vec3::new(cos * v.x + sin * v.z, v.y, sin * v.x + cos * v.z)
+    }
+
+    pub fn transmittance(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> f64 {
+        self.object.transmittance(&self.to_object(ray), interval, rng)
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval, rng: &mut ThreadRng) -> Option<HitRecord> {
+        let new_ray = self.to_object(ray);
 
         match self.object.hit(&new_ray, interval, rng)  {
             Some(mut hr) => {
-                hr.point = Point3::new(
-                    self.cos_theta * hr.point.x - self.sin_theta * hr.point.z,
-                    hr.point.y,
-                    self.sin_theta * hr.point.x + self.cos_theta * hr.point.z,
-                );
-                
-                hr.normal = Point3::new(
-                    self.cos_theta * hr.normal.x - self.sin_theta * hr.normal.z,
-                    hr.normal.y,
-                    self.sin_theta * hr.normal.x + self.cos_theta * hr.normal.z,
-                );
+                // Both rotating the point and rotating the next ray back round a few times.
+                hr.error = self.to_world_abs(&(hr.error + gamma(6) * hr.point.abs()));
+                hr.point = self.to_world(&hr.point);
+                hr.normal = self.to_world(&hr.normal);
+                hr.shading_normal = self.to_world(&hr.shading_normal);
+                hr.dpdu = self.to_world(&hr.dpdu);
+                hr.dpdv = self.to_world(&hr.dpdv);
                 Some(hr)
             },
             None => None
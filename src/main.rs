@@ -1,18 +1,30 @@
+mod backgrounds;
 mod camera;
 mod constants;
+mod distribution;
 mod hittables;
 mod interval;
 mod materials;
+mod onb;
 mod prelude;
 mod random;
 mod ray;
 mod vec3;
 mod perlin;
 mod texture;
+mod tone_map;
+mod spectrum;
+mod atmosphere;
 
+use crate::atmosphere::Atmosphere;
 use crate::camera::{CamArgs, Camera};
-use crate::hittables::hittables::HittableList;
+use crate::hittables::hittables::{HittableList, Hittables};
+use crate::hittables::mesh::{Mesh, Subdivision};
+use crate::hittables::sdf::Sdf;
+use crate::materials::phase::PhaseFunction;
 use crate::prelude::*;
+use crate::texture::{TextureFilter, WrapMode};
+use crate::tone_map::ToneMap;
 
 fn main() -> Result<(), Error> {
     match 9 {
@@ -26,6 +38,16 @@ fn main() -> Result<(), Error> {
         7 => cornell_box()?,
         8 => cornell_smoke()?,
         9 => final_scene(600, 5_000, 40)?,
+        10 => environment()?,
+        11 => dispersion()?,
+        12 => clouds()?,
+        13 => primitives()?,
+        14 => sdf_shapes()?,
+        15 => csg()?,
+        16 => ellipsoids()?,
+        17 => subdivision()?,
+        18 => bump_maps()?,
+        19 => texture_filtering()?,
         _ => final_scene(400, 50, 4)?,
     }
 
@@ -99,16 +121,8 @@ fn final_scene(image_width: usize, samples_per_pixel: i32, max_depth: i32) -> Re
     );
     
 
-    let mut boundary2 = HittableList::empty();
-    boundary2.add_sphere(
-        Point3::new(0.0, 0.0, 0.0),
-        5000.0,
-        glass.clone(),
-    );
-    world.add_solid_medium(boundary2.into_hittable(), 0.0001, Color3::new(1.0, 1.0, 1.0));
-
     //Earth and noise spheres
-    let earth_texture = Textures::image("assets/earthmap.jpg");
+    let earth_texture = Textures::image_or_placeholder("assets/earthmap.jpg");
     let earth_material = Materials::lambertian(earth_texture);
     world.add_sphere(Point3::new(400.0, 200.0, 400.0), 100.0, earth_material);
 
@@ -143,8 +157,12 @@ fn final_scene(image_width: usize, samples_per_pixel: i32, max_depth: i32) -> Re
         v_up: Vec3::new(0.0, 1.0, 0.0),
         defocus_angle: 0.0,
         focus_dist: 10.0,
-        background: Color3::new(0.0, 0.0, 0.0),
+        background: Background::solid(Color3::new(0.0, 0.0, 0.0)),
         thread_num: 8,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: Some(Atmosphere::new(0.0001, Color3::new(1.0, 1.0, 1.0), PhaseFunction::Isotropic, 5000.0)),
     };
 
     let camera = Camera::initilize(args);
@@ -152,6 +170,564 @@ fn final_scene(image_width: usize, samples_per_pixel: i32, max_depth: i32) -> Re
     Ok(())
 }
 
+#[allow(dead_code)]
+fn environment() -> Result<(), Error> {
+    println!("Rendering Environment");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let ground = Materials::lambertian_solid(Color3::new(0.5, 0.5, 0.5));
+    let diffuse = Materials::lambertian_solid(Color3::new(0.8, 0.3, 0.2));
+    let mirror = Materials::metal(Color3::new(0.9, 0.9, 0.9), 0.0);
+    let glass = Materials::dielectric(1.5);
+
+    // Spheres
+    world.add_sphere(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground);
+    world.add_sphere(Point3::new(-2.2, 1.0, 0.0), 1.0, diffuse);
+    world.add_sphere(Point3::new(0.0, 1.0, 0.0), 1.0, glass);
+    world.add_sphere(Point3::new(2.2, 1.0, 0.0), 1.0, mirror);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 30.0,
+        look_from: Point3::new(0.0, 2.5, 9.0),
+        look_at: Point3::new(0.0, 1.0, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::environment("assets/environment.hdr", 1.0)?,
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/environment.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn dispersion() -> Result<(), Error> {
+    println!("Rendering Dispersion");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let ground = Materials::lambertian_solid(Color3::new(0.8, 0.8, 0.8));
+    let light = Materials::emmiter_solid(Color3::new(8., 8., 8.));
+
+    // Objects
+    world.add_sphere(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground);
+    world.add_sphere(Point3::new(-1.3, 1.0, 0.0), 1.0, Materials::diamond());
+    world.add_sphere(Point3::new(1.3, 1.0, 0.0), 1.0, Materials::flint_glass());
+    world.add_quad(Point3::new(-1.5, 5.0, -3.0), Vec3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.5), light);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 500,
+        max_depth: 50,
+        vfov: 30.0,
+        look_from: Point3::new(0.0, 4.0, 9.0),
+        look_at: Point3::new(0.0, 0.7, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::solid(Color3::new(0.02, 0.02, 0.03)),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: true,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/dispersion.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn clouds() -> Result<(), Error> {
+    println!("Rendering Clouds");
+    let rng = &mut rand::thread_rng();
+    let mut world = HittableList::empty();
+
+    // Materials
+    let ground = Materials::lambertian_solid(Color3::new(0.4, 0.5, 0.3));
+    let white = Textures::solid_color(Color3::new(0.95, 0.95, 0.95));
+
+    // Objects
+    world.add_sphere(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground);
+    let boundary = Hittables::new_static_sphere(Point3::new(0.0, 3.0, 0.0), 2.5, Materials::dielectric(1.5));
+    world.add_noise_medium(boundary, 3.0, 2.0, white, PhaseFunction::two_lobe(0.8, -0.3, 0.8), rng);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 200,
+        max_depth: 50,
+        vfov: 40.0,
+        look_from: Point3::new(0.0, 2.0, 12.0),
+        look_at: Point3::new(0.0, 3.0, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/clouds.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn primitives() -> Result<(), Error> {
+    println!("Rendering Primitives");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let checker = Textures::checker(
+        0.5,
+        Textures::solid_color(Color3::new(0.2, 0.3, 0.1)),
+        Textures::solid_color(Color3::new(0.9, 0.9, 0.9)),
+    );
+    let ground = Materials::lambertian(checker);
+    let red = Materials::lambertian_solid(Color3::new(0.8, 0.2, 0.2));
+    let green = Materials::lambertian_solid(Color3::new(0.2, 0.7, 0.3));
+    let blue = Materials::lambertian_solid(Color3::new(0.2, 0.3, 0.8));
+    let gold = Materials::metal(Color3::new(0.9, 0.7, 0.3), 0.2);
+    let glass = Materials::dielectric(1.5);
+
+    // Objects
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground);
+    world.add_cylinder(Point3::new(-3.0, 0.0, 0.0), Vec3::new(0.0, 1.5, 0.0), 0.6, true, red);
+    world.add_cone(Point3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.8, 0.0), 0.7, true, green);
+    world.add_torus(Point3::new(1.0, 0.8, 0.0), Vec3::new(0.0, 0.3, 1.0), 0.6, 0.2, gold);
+    world.add_cylinder(Point3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 1.2, 0.0), 0.5, false, glass);
+    world.add_triangle(
+        Point3::new(-2.5, 0.0, -2.0),
+        Point3::new(-0.5, 0.0, -2.0),
+        Point3::new(-1.5, 2.0, -2.0),
+        blue.clone(),
+    );
+    world.add_disk(Point3::new(1.0, 1.2, -2.0), Vec3::new(0.0, 0.0, 1.0), 0.8, blue.clone());
+    world.add_ellipse(Point3::new(3.2, 1.0, -2.0), Vec3::new(0.9, 0.0, 0.0), Vec3::new(0.0, 0.5, 0.0), blue);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 3.0, 9.0),
+        look_at: Point3::new(0.0, 0.7, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/primitives.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn sdf_shapes() -> Result<(), Error> {
+    println!("Rendering SDF Shapes");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let ground = Materials::lambertian_solid(Color3::new(0.5, 0.5, 0.5));
+    let pink = Materials::lambertian_solid(Color3::new(0.8, 0.3, 0.5));
+    let teal = Materials::lambertian_solid(Color3::new(0.2, 0.6, 0.6));
+    let silver = Materials::metal(Color3::new(0.8, 0.8, 0.8), 0.1);
+    let gold = Materials::metal(Color3::new(0.9, 0.7, 0.3), 0.3);
+
+    // Objects
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground);
+
+    let blob = Sdf::sphere(0.6)
+        .smooth_union(Sdf::sphere(0.4).translate(Vec3::new(0.6, 0.5, 0.0)), 0.3)
+        .translate(Vec3::new(-3.0, 0.6, 0.0));
+    world.add_sdf(blob, pink);
+
+    let carved = Sdf::round_box(Vec3::new(0.6, 0.6, 0.6), 0.1)
+        .difference(Sdf::sphere(0.75))
+        .translate(Vec3::new(-1.0, 0.6, 0.0));
+    world.add_sdf(carved, teal);
+
+    let grid = Sdf::sphere(0.15)
+        .repeat(Vec3::new(0.4, 0.4, 0.4))
+        .intersection(Sdf::cuboid(Vec3::new(0.6, 0.6, 0.6)))
+        .translate(Vec3::new(1.0, 0.6, 0.0));
+    world.add_sdf(grid, silver);
+
+    let bulb = Sdf::mandelbulb(8.0, 12)
+        .scale(0.55)
+        .translate(Vec3::new(3.0, 0.65, 0.0));
+    world.add_sdf(bulb, gold);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 3.0, 9.0),
+        look_at: Point3::new(0.0, 0.6, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/sdf_shapes.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn csg() -> Result<(), Error> {
+    println!("Rendering CSG");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let checker = Textures::checker(
+        0.5,
+        Textures::solid_color(Color3::new(0.2, 0.3, 0.1)),
+        Textures::solid_color(Color3::new(0.9, 0.9, 0.9)),
+    );
+    let ground = Materials::lambertian(checker);
+    let glass = Materials::dielectric(1.5);
+    let steel = Materials::metal(Color3::new(0.7, 0.7, 0.75), 0.15);
+    let red = Materials::lambertian_solid(Color3::new(0.8, 0.2, 0.2));
+    let blue = Materials::lambertian_solid(Color3::new(0.2, 0.3, 0.8));
+    let gold = Materials::metal(Color3::new(0.9, 0.7, 0.3), 0.2);
+
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground);
+
+    // A lens, from two overlapping spheres
+    let lens = Hittables::intersection(
+        Hittables::new_static_sphere(Point3::new(-3.0, 1.0, 1.2), 1.5, glass.clone()),
+        Hittables::new_static_sphere(Point3::new(-3.0, 1.0, -1.2), 1.5, glass),
+    );
+    world.add(lens);
+
+    // A bowl, from a hollow shell with its top cut off
+    let shell = Hittables::difference(
+        Hittables::new_static_sphere(Point3::new(-1.0, 0.8, 0.0), 0.8, steel.clone()),
+        Hittables::new_static_sphere(Point3::new(-1.0, 0.8, 0.0), 0.7, steel.clone()),
+    );
+    let lid = HittableList::create_box(Point3::new(-2.0, 0.9, -1.0), Point3::new(0.0, 2.0, 1.0), steel.clone()).into_hittable();
+    world.add(Hittables::difference(shell, lid));
+
+    // A machined block, with a bore and a spherical pocket
+    let block = HittableList::create_box(Point3::new(0.4, 0.0, -0.6), Point3::new(1.6, 1.2, 0.6), steel).into_hittable();
+    let bore = Hittables::new_cylinder(Point3::new(1.0, 0.6, -1.0), Vec3::new(0.0, 0.0, 2.0), 0.3, true, red.clone());
+    let pocket = Hittables::new_static_sphere(Point3::new(1.6, 1.2, 0.6), 0.5, red);
+    world.add(Hittables::difference(Hittables::difference(block, bore), pocket));
+
+    // A capsule, from a cylinder and two spheres
+    let capsule = Hittables::union(
+        Hittables::new_cylinder(Point3::new(3.0, 0.4, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.4, true, blue.clone()),
+        Hittables::union(
+            Hittables::new_static_sphere(Point3::new(3.0, 0.4, 0.0), 0.4, blue.clone()),
+            Hittables::new_static_sphere(Point3::new(3.0, 1.4, 0.0), 0.4, blue),
+        ),
+    );
+    world.add(capsule);
+    world.add_sphere(Point3::new(-3.0, 0.3, -2.0), 0.3, gold);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 3.5, 9.0),
+        look_at: Point3::new(0.0, 0.7, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/csg.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn ellipsoids() -> Result<(), Error> {
+    println!("Rendering Ellipsoids");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let checker = Textures::checker(
+        0.5,
+        Textures::solid_color(Color3::new(0.2, 0.3, 0.1)),
+        Textures::solid_color(Color3::new(0.9, 0.9, 0.9)),
+    );
+    let ground = Materials::lambertian(checker);
+    let earth = Materials::lambertian(Textures::image("assets/earthmap.jpg")?);
+    let red = Materials::lambertian_solid(Color3::new(0.8, 0.2, 0.2));
+    let gold = Materials::metal(Color3::new(0.9, 0.7, 0.3), 0.1);
+
+    // Objects
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground);
+    world.add_ellipsoid(
+        Point3::new(-2.5, 0.8, 0.0),
+        Vec3::new(1.0, 0.8, 0.5),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        earth,
+    );
+    world.add_ellipsoid(
+        Point3::new(0.0, 1.0, 0.0),
+        Vec3::new(1.0, 0.3, 0.5),
+        Vec3::new(1.0, 1.0, 0.0),
+        Vec3::new(0.0, 1.0, 1.0),
+        gold,
+    );
+    world.add_moving_ellipsoid(
+        Point3::new(2.5, 0.6, 0.0),
+        Point3::new(2.5, 1.2, 0.0),
+        Vec3::new(0.4, 0.6, 0.4),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        red,
+    );
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 3.0, 9.0),
+        look_at: Point3::new(0.0, 0.8, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/ellipsoids.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn subdivision() -> Result<(), Error> {
+    println!("Rendering Subdivision");
+    let rng = &mut rand::thread_rng();
+    let mut world = HittableList::empty();
+
+    // Materials
+    let checker = Textures::checker(
+        0.5,
+        Textures::solid_color(Color3::new(0.2, 0.3, 0.1)),
+        Textures::solid_color(Color3::new(0.9, 0.9, 0.9)),
+    );
+    let ground = Materials::lambertian(checker);
+    let red = Materials::lambertian_solid(Color3::new(0.8, 0.2, 0.2));
+    let gold = Materials::metal(Color3::new(0.9, 0.7, 0.3), 0.2);
+    let stone = Materials::lambertian_solid(Color3::new(0.6, 0.55, 0.5));
+
+    // Meshes
+    let cube = |center: Point3, size: f64| {
+        let corners = (0..8)
+            .map(|i| center + size * Vec3::new((i & 1) as f64 - 0.5, ((i >> 1) & 1) as f64 - 0.5, ((i >> 2) & 1) as f64 - 0.5))
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1], vec![4, 5, 7, 6], vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3], vec![0, 4, 6, 2], vec![1, 3, 7, 5],
+        ];
+        Mesh::new(corners, faces)
+    };
+    let octahedron = Mesh::new(
+        vec![
+            Point3::new(0.0, -0.6, 0.0), Point3::new(0.0, 2.4, 0.0),
+            Point3::new(-1.5, 0.9, 0.0), Point3::new(1.5, 0.9, 0.0),
+            Point3::new(0.0, 0.9, -1.5), Point3::new(0.0, 0.9, 1.5),
+        ],
+        vec![
+            vec![1, 5, 3], vec![1, 3, 4], vec![1, 4, 2], vec![1, 2, 5],
+            vec![0, 3, 5], vec![0, 4, 3], vec![0, 2, 4], vec![0, 5, 2],
+        ],
+    );
+
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground);
+    world.add_mesh(&cube(Point3::new(-2.5, 0.8, 0.0), 1.4).subdivide(Subdivision::CatmullClark, 4), red, true);
+    world.add_mesh(&octahedron.subdivide(Subdivision::Loop, 4), gold, true);
+    let rock = cube(Point3::new(2.5, 0.8, 0.0), 1.4)
+        .subdivide(Subdivision::CatmullClark, 5)
+        .displace(&Textures::noise(4.0, rng), 0.3);
+    world.add_mesh(&rock, stone, false);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 3.0, 9.0),
+        look_at: Point3::new(0.0, 0.8, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/subdivision.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn bump_maps() -> Result<(), Error> {
+    println!("Rendering Bump Maps");
+    let rng = &mut rand::thread_rng();
+    let mut world = HittableList::empty();
+
+    // Materials
+    let plaster = Materials::bump_mapped(
+        Materials::lambertian_solid(Color3::new(0.7, 0.7, 0.65)),
+        Textures::noise(8.0, rng),
+        0.02,
+    );
+    let stone = Materials::bump_mapped(
+        Materials::lambertian_solid(Color3::new(0.8, 0.3, 0.2)),
+        Textures::noise(6.0, rng),
+        0.05,
+    );
+    let hammered = Materials::bump_mapped(Materials::gold(0.1), Textures::noise(10.0, rng), 0.03);
+    // A normal map tilting alternate tiles left and right, as an image normal map would.
+    let tiles = Materials::normal_mapped(
+        Materials::metal(Color3::new(0.8, 0.8, 0.8), 0.05),
+        Textures::checker(
+            0.3,
+            Textures::rgb(0.8, 0.5, 0.9),
+            Textures::rgb(0.2, 0.5, 0.9),
+        ),
+    );
+
+    // Objects
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), plaster);
+    world.add_sphere(Point3::new(-2.2, 1.0, 0.0), 1.0, stone);
+    world.add_sphere(Point3::new(0.0, 1.0, 0.0), 1.0, hammered);
+    world.add_quad(Point3::new(1.4, 0.0, -0.5), Vec3::new(1.6, 0.0, 0.5), Vec3::new(0.0, 2.0, 0.0), tiles);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 3.0, 9.0),
+        look_at: Point3::new(0.0, 0.9, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/bump_maps.ppm");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn texture_filtering() -> Result<(), Error> {
+    println!("Rendering Texture Filtering");
+    let mut world = HittableList::empty();
+
+    // Materials
+    let ground = Materials::lambertian(Textures::image_with("assets/earthmap.jpg", TextureFilter::Ewa, WrapMode::Repeat)?);
+    let nearest = Materials::lambertian(Textures::image_with("assets/earthmap.jpg", TextureFilter::Nearest, WrapMode::Clamp)?);
+    let bilinear = Materials::lambertian(Textures::image_with("assets/earthmap.jpg", TextureFilter::Bilinear, WrapMode::Clamp)?);
+    let trilinear = Materials::lambertian(Textures::image_with("assets/earthmap.jpg", TextureFilter::Trilinear, WrapMode::Clamp)?);
+
+    // Objects
+    world.add_plane(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), ground);
+    world.add_sphere(Point3::new(-2.2, 0.8, 0.0), 0.8, nearest);
+    world.add_sphere(Point3::new(0.0, 0.8, 0.0), 0.8, bilinear);
+    world.add_sphere(Point3::new(2.2, 0.8, 0.0), 0.8, trilinear);
+
+    // Camera
+    let args = CamArgs {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 16,
+        max_depth: 50,
+        vfov: 35.0,
+        look_from: Point3::new(0.0, 1.5, 9.0),
+        look_at: Point3::new(0.0, 0.8, 0.0),
+        v_up: Vec3::new(0.0, 1.0, 0.0),
+        defocus_angle: 0.0,
+        focus_dist: 10.0,
+        background: Background::sky(Vec3::new(1.0, 1.0, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.1),
+        thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
+    };
+
+    let camera = Camera::initilize(args);
+    let _ = camera.multi_render(world.create_bvh(), "images/texture_filtering.ppm");
+    Ok(())
+}
+
 #[allow(dead_code)]
 fn perlin_spheres() -> Result<(), Error> {
     println!("Rendering Perlin");
@@ -175,8 +751,12 @@ fn perlin_spheres() -> Result<(), Error> {
         v_up: Vec3::new(0.,1.,0.),
         defocus_angle: 0.0,
         focus_dist: 10.0,
-        background: Color3::new(0.7, 0.8, 1.),
+        background: Background::solid(Color3::new(0.7, 0.8, 1.)),
         thread_num: 3,
+        exposure: 0.0,
+        tone_map: ToneMap::Clamp,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -228,8 +808,12 @@ fn cornell_smoke() -> Result<(), Error> {
         v_up: Vec3::new(0.0, 1.0, 0.0),
         defocus_angle: 0.0,
         focus_dist: 10.0,
-        background: Color3::zero(),
+        background: Background::solid(Color3::zero()),
         thread_num: 1,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -278,8 +862,12 @@ fn cornell_box() -> Result<(), Error> {
         v_up: Vec3::new(0.0, 1.0, 0.0),
         defocus_angle: 0.0,
         focus_dist: 10.0,
-        background: Color3::zero(),
+        background: Background::solid(Color3::zero()),
         thread_num: 1,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -318,8 +906,12 @@ fn simple_light() -> Result<(), Error> {
         defocus_angle: 0.,
         focus_dist: 10.,
 
-        background: Color3::new(0., 0., 0.),
+        background: Background::solid(Color3::new(0., 0., 0.)),
         thread_num: 2,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -358,8 +950,12 @@ fn quads() -> Result<(), Error> {
         v_up: Vec3::new(0.,1.,0.),
         defocus_angle: 0.,
         focus_dist: 10.,
-        background: Color3::new(0.7, 0.8, 1.),
+        background: Background::solid(Color3::new(0.7, 0.8, 1.)),
         thread_num: 2,
+        exposure: 0.0,
+        tone_map: ToneMap::Clamp,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -371,7 +967,7 @@ fn quads() -> Result<(), Error> {
 #[allow(dead_code)]
 fn earth() -> Result<(), Error> {
     println!("Rendering Earth");
-    let earth_texture =Textures::image("assets/earthmap.jpg");
+    let earth_texture =Textures::image("assets/earthmap.jpg")?;
     let earth_surface = Materials::lambertian(earth_texture);
     let mut world = HittableList::empty();
     world.add_sphere(Point3::new(0.,0.,0.), 2., earth_surface);
@@ -388,8 +984,12 @@ fn earth() -> Result<(), Error> {
         v_up: Vec3::new(0.,1.,0.),
         defocus_angle: 0.,
         focus_dist: 10.,
-        background: Color3::new(0.7, 0.8, 1.),
+        background: Background::solid(Color3::new(0.7, 0.8, 1.)),
         thread_num: 2,
+        exposure: 0.0,
+        tone_map: ToneMap::Clamp,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -425,8 +1025,12 @@ fn temp1() -> Result<(), Error> {
         v_up: Vec3::new(0., 1., 0.),
         defocus_angle: 0.,
         focus_dist: 10.,
-        background: Color3::new(0.7, 0.8, 1.),
+        background: Background::solid(Color3::new(0.7, 0.8, 1.)),
         thread_num: 4,
+        exposure: 0.0,
+        tone_map: ToneMap::Clamp,
+        spectral: false,
+        atmosphere: None,
     };
     let camera = Camera::initilize(args);
     let _ = camera.render(world.create_bvh(), "images/temp1.ppm");
@@ -492,8 +1096,12 @@ fn final1() -> Result<(), Error> {
         v_up: Vec3::new(0., 1., 0.),
         defocus_angle: 0.6,
         focus_dist: 10.,
-        background: Color3::new(0.7, 0.8, 1.),
+        background: Background::sky(Vec3::new(-0.4, 0.35, 0.5), 3.0, Color3::new(0.3, 0.3, 0.3), 0.15),
         thread_num: 6,
+        exposure: 0.0,
+        tone_map: ToneMap::Aces,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -569,8 +1177,12 @@ fn temp2() -> Result<(), Error> {
         v_up: Vec3::new(0., 1., 0.),
         defocus_angle: 0.6,
         focus_dist: 10.,
-        background: Color3::new(0.7, 0.8, 1.),
+        background: Background::solid(Color3::new(0.7, 0.8, 1.)),
         thread_num: 2,
+        exposure: 0.0,
+        tone_map: ToneMap::Clamp,
+        spectral: false,
+        atmosphere: None,
     };
 
     let camera = Camera::initilize(args);
@@ -0,0 +1,124 @@
+use crate::materials::materials::ScatterRecord;
+use crate::prelude::*;
+
+/// The step in texture coordinates used to take the slope of a height map.
+const BUMP_DELTA: f64 = 5e-4;
+
+/// How a `Bump` material perturbs the normal.
+///
+/// # Variants
+/// - `Height`: A scalar height map, read from the luminance of a texture and scaled to world units.
+/// - `Normal`: A tangent-space normal map, whose red, green and blue channels hold the
+///   components along `dpdu`, `dpdv` and the normal, mapped from [-1, 1] to [0, 1].
+#[derive(Debug, Clone)]
+pub(crate) enum BumpMap {
+    Height { tex: Arc<Textures>, scale: f64 },
+    Normal(Arc<Textures>),
+}
+
+/// Adds surface detail to any material by perturbing its shading normal, without changing
+/// the geometry or the geometric normal.
+///
+/// The perturbed normal is oriented by the tangents `dpdu` and `dpdv` of the hit, so the
+/// map follows the texture coordinates of the surface.
+#[derive(Debug, Clone)]
+pub(crate) struct Bump {
+    /// The material shaded with the perturbed normal.
+    base: Arc<Materials>,
+    /// The map that perturbs the normal.
+    map: BumpMap,
+}
+
+impl Bump {
+    /// Creates a new `Bump` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The material shaded with the perturbed normal.
+    /// * `map` - The height or normal map.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Bump`.
+    pub(crate) fn new(base: Arc<Materials>, map: BumpMap) -> Self {
+        Self { base, map }
+    }
+
+    /// Returns a copy of the hit record whose shading normal is perturbed by the map.
+    fn shade(&self, hit_record: &HitRecord) -> HitRecord {
+        let outward = hit_record.outward_shading_normal();
+        let (dpdu, dpdv) = tangents(hit_record, &outward);
+
+        let perturbed = match &self.map {
+            BumpMap::Height { tex, scale } => {
+                let height = |du: f64, dv: f64| {
+                    let point = hit_record.point + du * dpdu + dv * dpdv;
+                    scale * tex.value(hit_record.u + du, hit_record.v + dv, &point).luminance()
+                };
+                let base = height(0., 0.);
+                let along_u = dpdu + (height(BUMP_DELTA, 0.) - base) / BUMP_DELTA * outward;
+                let along_v = dpdv + (height(0., BUMP_DELTA) - base) / BUMP_DELTA * outward;
+                along_u.cross(&along_v)
+            }
+            BumpMap::Normal(tex) => {
+                let n = 2. * tex.sample(hit_record) - Vec3::new(1., 1., 1.);
+                let t = (dpdu - dpdu.dot(&outward) * outward).normalize();
+                let mut b = outward.cross(&t);
+                if b.dot(&dpdv) < 0. {
+                    b = -1. * b;
+                }
+                n.x * t + n.y * b + n.z * outward
+            }
+        };
+
+        let mut shaded = hit_record.clone();
+        if perturbed.norm() > 0. {
+            let mut normal = perturbed.normalize();
+            if normal.dot(&outward) < 0. {
+                normal = -1. * normal;
+            }
+            shaded.set_shading_normal(&normal);
+        }
+        shaded
+    }
+
+    /// Computes how a ray scatters when it hits the material.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point.
+    /// * `rng` - A random number generator used for sampling the base material.
+    ///
+    /// # Returns
+    ///
+    /// The `ScatterRecord` of the base material shaded with the perturbed normal.
+    pub(crate) fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, rng: &mut ThreadRng) -> Option<ScatterRecord> {
+        self.base.scatter(ray_in, &self.shade(hit_record), rng)
+    }
+
+    /// Evaluates the BSDF of the base material, shaded with the perturbed normal.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        self.base.eval(ray_in, &self.shade(hit_record), direction)
+    }
+
+    /// Returns the pdf of the base material, shaded with the perturbed normal.
+    pub(crate) fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        self.base.pdf(ray_in, &self.shade(hit_record), direction)
+    }
+
+    /// Returns the light emitted by the base material.
+    pub(crate) fn emitted(&self, hit_record: &HitRecord) -> Color3 {
+        self.base.emitted(&self.shade(hit_record))
+    }
+}
+
+/// The tangents of a hit, or an arbitrary frame around the normal where the surface has none.
+fn tangents(hit_record: &HitRecord, outward: &Vec3) -> (Vec3, Vec3) {
+    if hit_record.dpdu.cross(&hit_record.dpdv).norm() > 0. {
+        (hit_record.dpdu, hit_record.dpdv)
+    } else {
+        let basis = Onb::new(outward);
+        (basis.u, basis.v)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::materials::fresnel;
+use crate::materials::materials::ScatterRecord;
+use crate::materials::microfacet::{self, Ggx};
+use crate::prelude::*;
+
+/// The bounds on the probability of sampling the coat rather than the base.
+///
+/// Following Fresnel exactly would almost never sample the coat at normal incidence,
+/// which makes rough coat highlights noisy.
+const MIN_COAT_PROBABILITY: f64 = 0.1;
+const MAX_COAT_PROBABILITY: f64 = 0.9;
+
+/// Layers a smooth or rough dielectric interface, like varnish or a clear coat, over any material.
+///
+/// Light is either reflected by the interface, or refracted into it, scattered by the base
+/// and refracted back out, so the base is weighted by `(1 - F(wo)) * (1 - F(wi))`.
+#[derive(Debug, Clone)]
+pub(crate) struct Coated {
+    /// The material underneath the coat.
+    base: Arc<Materials>,
+    /// The index of refraction of the coat.
+    refraction_index: f64,
+    /// The microfacet distribution describing the roughness of the coat.
+    distribution: Ggx,
+}
+
+impl Coated {
+    /// Creates a new `Coated` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The material underneath the coat.
+    /// * `refraction_index` - The index of refraction of the coat.
+    /// * `roughness` - The roughness of the coat in [0, 1]; `0.0` is perfectly smooth.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Coated`.
+    pub(crate) fn new(base: Arc<Materials>, refraction_index: f64, roughness: f64) -> Self {
+        Self {
+            base,
+            refraction_index,
+            distribution: Ggx::new(roughness, roughness),
+        }
+    }
+
+    /// Returns the fraction of light the coat transmits for a direction with cosine `cos_theta` to the normal.
+    fn transmitted(&self, cos_theta: f64) -> f64 {
+        1. - fresnel::dielectric(cos_theta.abs(), self.refraction_index)
+    }
+
+    /// Returns the probability of sampling the coat for the outgoing direction `wo`.
+    fn coat_probability(&self, wo: &Vec3) -> f64 {
+        fresnel::dielectric(wo.z, self.refraction_index).clamp(MIN_COAT_PROBABILITY, MAX_COAT_PROBABILITY)
+    }
+
+    /// Computes how a ray scatters when it hits the coated material.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point.
+    /// * `rng` - A random number generator used for choosing a layer and sampling it.
+    ///
+    /// # Returns
+    ///
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
+    /// If `None` is returned, the ray is absorbed.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        let frame = Onb::new(&hit_record.shading_normal);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        if wo.z <= 0. {
+            return None;
+        }
+        let coat_probability = self.coat_probability(&wo);
+
+        let scattered = if gen_01(rng) < coat_probability {
+            if self.distribution.is_smooth() {
+                let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+                let weight = (1. - self.transmitted(wo.z)) / coat_probability;
+                return Some(ScatterRecord {
+                    attenuation: Color3::new(weight, weight, weight),
+                    scattered: hit_record.spawn_ray(frame.transform(&wi), ray_in.time),
+                    pdf: None,
+                });
+            }
+
+            let wm = self.distribution.sample_visible_normal(&wo, rng);
+            let wi = microfacet::reflect(&wo, &wm);
+            if wi.z <= 0. {
+                return None;
+            }
+            hit_record.spawn_ray(frame.transform(&wi), ray_in.time)
+        } else {
+            let mut srec = self.base.scatter(ray_in, hit_record, rng)?;
+            if srec.pdf.is_none() {
+                let wi = frame.inverse_transform(&srec.scattered.direction.normalize());
+                let weight = self.transmitted(wo.z) * self.transmitted(wi.z) / (1. - coat_probability);
+                srec.attenuation = srec.attenuation * weight;
+                return Some(srec);
+            }
+            srec.scattered
+        };
+
+        // Either layer could have produced a non-specular direction, so weight it by both.
+        let pdf = self.pdf(ray_in, hit_record, &scattered.direction);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(ScatterRecord {
+            attenuation: self.eval(ray_in, hit_record, &scattered.direction) / pdf,
+            scattered,
+            pdf: Some(pdf),
+        })
+    }
+
+    /// Evaluates the BSDF times the cosine term for light arriving from `direction`.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        let frame = Onb::new(&hit_record.shading_normal);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        let wi = frame.inverse_transform(&direction.normalize());
+        if wo.z <= 0. {
+            return Color3::zero();
+        }
+
+        let base = self.base.eval(ray_in, hit_record, direction)
+            * (self.transmitted(wo.z) * self.transmitted(wi.z));
+        if self.distribution.is_smooth() || wi.z <= 0. {
+            return base;
+        }
+
+        let wm = (wo + wi).normalize();
+        let reflectance = fresnel::dielectric(wo.dot(&wm), self.refraction_index);
+        let coat = self.distribution.d(&wm) * self.distribution.g2(&wo, &wi) * reflectance / (4. * wo.z);
+        base + Color3::new(coat, coat, coat)
+    }
+
+    /// Returns the pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        let frame = Onb::new(&hit_record.shading_normal);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        let wi = frame.inverse_transform(&direction.normalize());
+        if wo.z <= 0. {
+            return 0.;
+        }
+        let coat_probability = self.coat_probability(&wo);
+
+        let base = (1. - coat_probability) * self.base.pdf(ray_in, hit_record, direction);
+        if self.distribution.is_smooth() || wi.z <= 0. {
+            return base;
+        }
+
+        let wm = (wo + wi).normalize();
+        base + coat_probability * self.distribution.visible_d(&wo, &wm) / (4. * wo.dot(&wm))
+    }
+
+    /// Returns the light emitted by the base.
+    ///
+    /// The emission is looked up without the direction it leaves in, so the coat does not
+    /// attenuate it: coated emitters are as bright as their base.
+    pub(crate) fn emitted(&self, hit_record: &HitRecord) -> Color3 {
+        self.base.emitted(hit_record)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::materials::fresnel;
+use crate::materials::materials::ScatterRecord;
+use crate::materials::microfacet::{self, Ggx};
+use crate::materials::thin_film::ThinFilm;
+use crate::prelude::*;
+
+/// Represents a rough conductor (metal) using the GGX microfacet model.
+///
+/// Unlike `Metal`, the reflectance comes from the complex index of refraction of a real metal,
+/// so the color shifts towards white at grazing angles, and roughness is energy conserving.
+#[derive(Debug, Clone)]
+pub(crate) struct Conductor {
+    /// The real part of the index of refraction, per color channel.
+    eta: Color3,
+    /// The absorption coefficient (imaginary part of the index of refraction), per color channel.
+    k: Color3,
+    /// The microfacet distribution describing the surface roughness.
+    distribution: Ggx,
+    /// An optional thin film coating the metal, e.g. an anodized oxide layer.
+    film: Option<ThinFilm>,
+}
+
+impl Conductor {
+    /// Creates a new `Conductor` from its optical constants and roughness.
+    ///
+    /// # Arguments
+    ///
+    /// * `eta` - The real part of the index of refraction, per color channel.
+    /// * `k` - The absorption coefficient, per color channel.
+    /// * `roughness_u` - The roughness along the tangent direction, in [0, 1].
+    /// * `roughness_v` - The roughness along the bitangent direction, in [0, 1].
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Conductor`.
+    pub(crate) fn new(eta: Color3, k: Color3, roughness_u: f64, roughness_v: f64) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: Ggx::new(roughness_u, roughness_v),
+            film: None,
+        }
+    }
+
+    /// Coats the conductor with a thin film.
+    ///
+    /// # Arguments
+    ///
+    /// * `film` - The film covering the metal.
+    ///
+    /// # Returns
+    ///
+    /// The coated conductor.
+    pub(crate) fn with_film(self, film: ThinFilm) -> Self {
+        Self {
+            film: Some(film),
+            ..self
+        }
+    }
+
+    /// Computes the Fresnel reflectance, including interference in the film if there is one.
+    fn fresnel(&self, cos_theta: f64, ray_in: &Ray, hit_record: &HitRecord) -> Color3 {
+        match &self.film {
+            Some(film) => film.reflectance(cos_theta, 1., &self.eta, &self.k, hit_record, ray_in.wavelength),
+            None => fresnel::conductor(cos_theta, &self.eta, &self.k),
+        }
+    }
+
+    /// Computes how a ray scatters when it hits the conductor.
+    ///
+    /// The microfacet normal is drawn from the distribution of visible normals, so the
+    /// throughput weight only depends on Fresnel and the masking-shadowing ratio.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point, including the normal and hit location.
+    /// * `rng` - A random number generator used for sampling microfacet normals.
+    ///
+    /// # Returns
+    ///
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
+    /// If `None` is returned, the ray is absorbed.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        let frame = Self::frame(hit_record);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        if wo.z <= 0. {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+            return Some(ScatterRecord {
+                attenuation: self.fresnel(wo.z, ray_in, hit_record),
+                scattered: hit_record.spawn_ray(frame.transform(&wi), ray_in.time),
+                pdf: None,
+            });
+        }
+
+        let wm = self.distribution.sample_visible_normal(&wo, rng);
+        let wi = microfacet::reflect(&wo, &wm);
+        if wi.z <= 0. {
+            return None;
+        }
+
+        let fresnel = self.fresnel(wo.dot(&wm), ray_in, hit_record);
+        let attenuation = fresnel * (self.distribution.g2(&wo, &wi) / self.distribution.g1(&wo));
+        let pdf = self.distribution.visible_d(&wo, &wm) / (4. * wo.dot(&wm));
+
+        Some(ScatterRecord {
+            attenuation,
+            scattered: hit_record.spawn_ray(frame.transform(&wi), ray_in.time),
+            pdf: Some(pdf),
+        })
+    }
+
+    /// Builds the local shading frame for a hit, with `u` along the surface tangent `dpdu` so
+    /// that `roughness_u` and `roughness_v` follow the surface parameterization.
+    fn frame(hit_record: &HitRecord) -> Onb {
+        Onb::from_tangent(&hit_record.shading_normal, &hit_record.dpdu)
+    }
+
+    /// Evaluates the BRDF times the cosine term for light arriving from `direction`.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        if self.distribution.is_smooth() {
+            return Color3::zero();
+        }
+
+        let frame = Self::frame(hit_record);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        let wi = frame.inverse_transform(&direction.normalize());
+        if wo.z <= 0. || wi.z <= 0. {
+            return Color3::zero();
+        }
+
+        let wm = (wo + wi).normalize();
+        let fresnel = self.fresnel(wo.dot(&wm), ray_in, hit_record);
+        fresnel * (self.distribution.d(&wm) * self.distribution.g2(&wo, &wi) / (4. * wo.z))
+    }
+
+    /// Returns the pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.;
+        }
+
+        let frame = Self::frame(hit_record);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        let wi = frame.inverse_transform(&direction.normalize());
+        if wo.z <= 0. || wi.z <= 0. {
+            return 0.;
+        }
+
+        let wm = (wo + wi).normalize();
+        self.distribution.visible_d(&wo, &wm) / (4. * wo.dot(&wm))
+    }
+}
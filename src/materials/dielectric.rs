@@ -1,11 +1,57 @@
+use crate::materials::materials::ScatterRecord;
+use crate::materials::thin_film::ThinFilm;
 use crate::prelude::*;
 
 /// Represents a dielectric material with a given refraction index.
 /// This material simulates the behavior of transparent materials like glass or water.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub(crate) struct Dielectric {
-    /// The index of refraction of the material.
+    /// The index of refraction of the material, used for rays that carry no wavelength.
     refraction_index: f64,
+    /// How the index of refraction varies with wavelength in spectral mode.
+    dispersion: Dispersion,
+    /// An optional thin film on the surface, e.g. the soap film of a bubble.
+    film: Option<ThinFilm>,
+}
+
+/// A model of how a dielectric's index of refraction depends on wavelength.
+///
+/// Wavelengths are plugged into both formulas in micrometers, as their coefficients are usually published.
+#[derive(Debug, Copy, Clone)]
+pub enum Dispersion {
+    /// The index of refraction is the same at every wavelength.
+    None,
+    /// Cauchy's equation `n = a + b / λ²`.
+    Cauchy { a: f64, b: f64 },
+    /// The Sellmeier equation `n² = 1 + Σ bᵢ λ² / (λ² - cᵢ)`.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+/// The wavelength of the Fraunhofer d line in nanometers, used to quote a single index of refraction.
+const D_LINE: f64 = 587.56;
+
+impl Dispersion {
+    /// Evaluates the index of refraction at a wavelength.
+    ///
+    /// # Arguments
+    ///
+    /// * `wavelength` - The wavelength in nanometers.
+    ///
+    /// # Returns
+    ///
+    /// The index of refraction, or `None` if the material is not dispersive.
+    fn refraction_index(&self, wavelength: f64) -> Option<f64> {
+        let l = wavelength * 1e-3;
+        let l2 = l * l;
+        match self {
+            Self::None => None,
+            Self::Cauchy { a, b } => Some(a + b / l2),
+            Self::Sellmeier { b, c } => {
+                let n2 = 1. + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>();
+                Some(n2.max(1.).sqrt())
+            }
+        }
+    }
 }
 
 /// Computes the reflectance using Schlick's approximation.
@@ -35,7 +81,51 @@ impl Dielectric {
     ///
     /// A new instance of `Dielectric`.
     pub(crate) fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self {
+            refraction_index,
+            dispersion: Dispersion::None,
+            film: None,
+        }
+    }
+
+    /// Creates a new dispersive `Dielectric` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispersion` - How the index of refraction varies with wavelength.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Dielectric`, whose index in RGB mode is the one at the d line.
+    pub(crate) fn dispersive(dispersion: Dispersion) -> Self {
+        Self {
+            refraction_index: dispersion.refraction_index(D_LINE).unwrap_or(1.),
+            dispersion,
+            film: None,
+        }
+    }
+
+    /// Coats the dielectric with a thin film.
+    ///
+    /// # Arguments
+    ///
+    /// * `film` - The film covering the surface.
+    ///
+    /// # Returns
+    ///
+    /// The coated dielectric.
+    pub(crate) fn with_film(self, film: ThinFilm) -> Self {
+        Self {
+            film: Some(film),
+            ..self
+        }
+    }
+
+    /// Returns the index of refraction seen by a ray.
+    fn refraction_index(&self, ray: &Ray) -> f64 {
+        ray.wavelength
+            .and_then(|wavelength| self.dispersion.refraction_index(wavelength))
+            .unwrap_or(self.refraction_index)
     }
 
     /// Computes how a ray scatters when it hits the dielectric material.
@@ -48,31 +138,64 @@ impl Dielectric {
     ///
     /// # Returns
     ///
-    /// An optional tuple containing the attenuation color and the scattered ray.
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
     /// If `None` is returned, the ray is absorbed.
     pub(crate) fn scatter(
         &self,
         ray_in: &Ray,
         hit_record: &HitRecord,
         rng: &mut ThreadRng,
-    ) -> Option<(Color3, Ray)> {
+    ) -> Option<ScatterRecord> {
+        let refraction_index = self.refraction_index(ray_in);
         let ri = if hit_record.front_face {
-            1.0 / self.refraction_index
+            1.0 / refraction_index
         } else {
-            self.refraction_index
+            refraction_index
         };
         let unit_direction = ray_in.direction.normalize();
 
-        let cos_theta = f64::min(-1.0 * unit_direction.dot(&hit_record.normal), 1.0);
+        let cos_theta = f64::min(-unit_direction.dot(&hit_record.shading_normal), 1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
-        let direction = if ri * sin_theta > 1.0 || reflectance(cos_theta, ri) > gen_01(rng) {
-            unit_direction.reflect(&hit_record.normal)
+        if ri * sin_theta > 1.0 {
+            return Some(ScatterRecord {
+                attenuation: Color3::new(1.0, 1.0, 1.0),
+                scattered: hit_record.spawn_ray(unit_direction.reflect(&hit_record.shading_normal), ray_in.time),
+                pdf: None,
+            });
+        }
+
+        // A film makes the reflectance differ per channel, so the choice between reflection
+        // and refraction follows the average and the attenuation corrects for each channel.
+        let reflected = match &self.film {
+            Some(film) => {
+                let (outside, inside) = if hit_record.front_face {
+                    (1.0, refraction_index)
+                } else {
+                    (refraction_index, 1.0)
+                };
+                let eta = Color3::new(inside, inside, inside);
+                film.reflectance(cos_theta, outside, &eta, &Color3::zero(), hit_record, ray_in.wavelength)
+            }
+            None => {
+                let r = reflectance(cos_theta, ri);
+                Color3::new(r, r, r)
+            }
+        };
+        let probability = (reflected.x + reflected.y + reflected.z) / 3.0;
+
+        let (direction, attenuation) = if probability > gen_01(rng) {
+            (unit_direction.reflect(&hit_record.shading_normal), reflected / probability)
         } else {
-            unit_direction.refract(&hit_record.normal, ri)
+            let transmitted = Color3::new(1.0, 1.0, 1.0) - reflected;
+            (unit_direction.refract(&hit_record.shading_normal, ri), transmitted / (1.0 - probability))
         };
 
-        let scattered = Ray::new_time(hit_record.point, direction, ray_in.time);
-        Some((Color3::new(1.0, 1.0, 1.0), scattered))
+        let scattered = hit_record.spawn_ray(direction, ray_in.time);
+        Some(ScatterRecord {
+            attenuation,
+            scattered,
+            pdf: None,
+        })
     }
 }
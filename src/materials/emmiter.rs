@@ -1,22 +1,41 @@
 use crate::prelude::*;
 
+/// A diffuse area light, emitting the same radiance in every direction.
 #[derive(Debug, Clone)]
 pub struct Diffuse {
+    /// The emitted radiance, before scaling by `strength`.
     texture: Arc<Textures>,
+    /// The scale applied to the texture.
+    strength: f64,
+    /// Whether the back face emits too.
+    two_sided: bool,
 }
 
 impl Diffuse {
-    // pub fn new(texture : Arc<Textures>) -> Self {
-    //     Self {texture}
-    // }
+    /// Creates a new `Diffuse` emitter.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The emitted radiance, before scaling by `strength`.
+    /// * `strength` - The scale applied to the texture.
+    /// * `two_sided` - Whether the back face emits too.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Diffuse`.
+    pub fn new(texture : Arc<Textures>, strength: f64, two_sided: bool) -> Self {
+        Self {texture, strength, two_sided}
+    }
 
     pub fn solid(color: Color3) -> Self {
-        Self {
-            texture: Textures::solid_color(color)
-        }
+        Self::new(Textures::solid_color(color), 1., true)
     }
 
-    pub fn emitted(&self, u : f64, v : f64, p : &Point3) -> Color3 {
-        self.texture.value(u, v, p)
+    /// Returns the radiance emitted at a hit, which is zero on the back of a one-sided emitter.
+    pub fn emitted(&self, hit_record: &HitRecord) -> Color3 {
+        if !self.two_sided && !hit_record.front_face {
+            return Color3::zero();
+        }
+        self.texture.sample(hit_record) * self.strength
     }
 }
@@ -0,0 +1,261 @@
+use crate::prelude::*;
+
+/// Computes the unpolarized Fresnel reflectance of a conductor for each color channel.
+///
+/// # Arguments
+///
+/// * `cos_theta` - The cosine of the angle between the incident direction and the normal.
+/// * `eta` - The real part of the conductor's index of refraction, per channel.
+/// * `k` - The imaginary part (absorption coefficient) of the index of refraction, per channel.
+///
+/// # Returns
+///
+/// The reflectance of each channel, in [0, 1].
+pub(crate) fn conductor(cos_theta: f64, eta: &Color3, k: &Color3) -> Color3 {
+    let cos_theta = cos_theta.clamp(0., 1.);
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_theta * cos_theta;
+        let sin2 = 1. - cos2;
+        let eta2 = eta * eta;
+        let k2 = k * k;
+
+        let t0 = eta2 - k2 - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4. * eta2 * k2).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.).sqrt();
+        let t2 = 2. * cos_theta * a;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+
+        0.5 * (rp + rs)
+    };
+
+    Color3::new(
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    )
+}
+
+/// Computes the exact unpolarized Fresnel reflectance at a dielectric interface.
+///
+/// # Arguments
+///
+/// * `cos_theta_i` - The cosine of the angle between the incident direction and the normal.
+///   Negative values mean the incident direction is on the inside of the interface.
+/// * `eta` - The relative index of refraction, inside over outside.
+///
+/// # Returns
+///
+/// The reflectance in [0, 1]; `1.0` under total internal reflection.
+pub(crate) fn dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let (cos_theta_i, eta) = if cos_theta_i < 0. {
+        (-cos_theta_i, 1. / eta)
+    } else {
+        (cos_theta_i, eta)
+    };
+    let cos_theta_i = cos_theta_i.min(1.);
+
+    let sin2_theta_t = (1. - cos_theta_i * cos_theta_i) / (eta * eta);
+    if sin2_theta_t >= 1. {
+        return 1.;
+    }
+    let cos_theta_t = (1. - sin2_theta_t).sqrt();
+
+    let r_parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    0.5 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+/// Computes Schlick's approximation of the Fresnel reflectance for a tinted normal-incidence reflectance.
+///
+/// # Arguments
+///
+/// * `cos_theta` - The cosine of the angle between the incident direction and the normal.
+/// * `f0` - The reflectance at normal incidence, per channel.
+///
+/// # Returns
+///
+/// The reflectance of each channel, blending from `f0` towards white at grazing angles.
+pub(crate) fn schlick(cos_theta: f64, f0: &Color3) -> Color3 {
+    let weight = (1. - cos_theta.clamp(0., 1.)).powi(5);
+    *f0 + (Color3::new(1., 1., 1.) - *f0) * weight
+}
+
+/// A minimal complex number, used for the amplitudes of the thin-film equations.
+#[derive(Debug, Copy, Clone)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn real(re: f64) -> Self {
+        Self::new(re, 0.)
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+
+    fn div(self, other: Self) -> Self {
+        let denom = other.norm_sqr();
+        Self::new(
+            (self.re * other.re + self.im * other.im) / denom,
+            (self.im * other.re - self.re * other.im) / denom,
+        )
+    }
+
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// The principal square root.
+    fn sqrt(self) -> Self {
+        let r = self.norm_sqr().sqrt();
+        let re = (0.5 * (r + self.re)).max(0.).sqrt();
+        let im = (0.5 * (r - self.re)).max(0.).sqrt();
+        Self::new(re, if self.im < 0. { -im } else { im })
+    }
+
+    /// `e^(i * self)`.
+    fn exp_i(self) -> Self {
+        let scale = (-self.im).exp();
+        Self::new(scale * self.re.cos(), scale * self.re.sin())
+    }
+}
+
+/// The cosine of the refracted angle in medium `n_t`, given `n_i * sin(theta_i)` squared.
+fn refracted_cos(n_sin_sqr: f64, n_t: Complex) -> Complex {
+    Complex::real(1.).sub(Complex::real(n_sin_sqr).div(n_t.mul(n_t))).sqrt()
+}
+
+/// The s- and p-polarized Fresnel amplitude coefficients between two media.
+fn amplitudes(n_i: Complex, cos_i: Complex, n_t: Complex, cos_t: Complex) -> (Complex, Complex) {
+    let a = n_i.mul(cos_i);
+    let b = n_t.mul(cos_t);
+    let s = a.sub(b).div(a.add(b));
+    let c = n_t.mul(cos_i);
+    let d = n_i.mul(cos_t);
+    let p = c.sub(d).div(c.add(d));
+    (s, p)
+}
+
+/// Computes the reflectance of a substrate covered by a thin film, summing every internal
+/// reflection of the film (Airy's formula) for a single wavelength.
+///
+/// # Arguments
+///
+/// * `cos_theta` - The cosine of the angle of incidence, in the outside medium.
+/// * `outside_ior` - The index of refraction of the medium the light arrives from.
+/// * `film_ior` - The index of refraction of the film.
+/// * `thickness` - The thickness of the film in nanometers.
+/// * `eta` - The real part of the substrate's index of refraction.
+/// * `k` - The imaginary part of the substrate's index of refraction; zero for dielectrics.
+/// * `wavelength` - The wavelength in nanometers.
+///
+/// # Returns
+///
+/// The unpolarized reflectance in [0, 1].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn thin_film(
+    cos_theta: f64,
+    outside_ior: f64,
+    film_ior: f64,
+    thickness: f64,
+    eta: f64,
+    k: f64,
+    wavelength: f64,
+) -> f64 {
+    let cos_theta = cos_theta.clamp(0., 1.);
+    let n_sin_sqr = outside_ior * outside_ior * (1. - cos_theta * cos_theta);
+
+    let n1 = Complex::real(outside_ior);
+    let n2 = Complex::real(film_ior);
+    let n3 = Complex::new(eta, k);
+    let cos1 = Complex::real(cos_theta);
+    let cos2 = refracted_cos(n_sin_sqr, n2);
+    let cos3 = refracted_cos(n_sin_sqr, n3);
+
+    let (r12_s, r12_p) = amplitudes(n1, cos1, n2, cos2);
+    let (r23_s, r23_p) = amplitudes(n2, cos2, n3, cos3);
+
+    // The phase difference accumulated by one round trip through the film.
+    let phase = Complex::real(4. * PI * thickness / wavelength).mul(n2.mul(cos2)).exp_i();
+    let airy = |r12: Complex, r23: Complex| {
+        let r23 = r23.mul(phase);
+        r12.add(r23).div(Complex::real(1.).add(r12.mul(r23))).norm_sqr()
+    };
+
+    (0.5 * (airy(r12_s, r23_s) + airy(r12_p, r23_p))).clamp(0., 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conductor_matches_normal_incidence_formula() {
+        let (eta, k) = (Color3::new(0.2, 1.1, 1.6), Color3::new(3.9, 2.4, 1.6));
+        let f = conductor(1., &eta, &k);
+        for c in 0..3 {
+            let expected = ((eta[c] - 1.).powi(2) + k[c] * k[c]) / ((eta[c] + 1.).powi(2) + k[c] * k[c]);
+            assert!((f[c] - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn conductor_reflects_everything_at_grazing_angles() {
+        let f = conductor(0., &Color3::new(0.2, 1.1, 1.6), &Color3::new(3.9, 2.4, 1.6));
+        for c in 0..3 {
+            assert!((f[c] - 1.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn conductor_stays_in_unit_range() {
+        for i in 0..=100 {
+            let f = conductor(i as f64 / 100., &Color3::new(1.66, 0.88, 0.52), &Color3::new(9.2, 6.3, 4.8));
+            for c in 0..3 {
+                assert!((0. ..=1.).contains(&f[c]));
+            }
+        }
+    }
+
+    #[test]
+    fn dielectric_matches_normal_incidence_formula() {
+        let eta: f64 = 1.5;
+        let expected = ((eta - 1.) / (eta + 1.)).powi(2);
+        assert!((dielectric(1., eta) - expected).abs() < 1e-12);
+        assert!((dielectric(-1., eta) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn dielectric_limits() {
+        // Grazing incidence reflects everything, as does total internal reflection from inside.
+        assert!((dielectric(0., 1.5) - 1.).abs() < 1e-12);
+        assert_eq!(dielectric(-0.5, 1.5), 1.);
+        // Matched indices have no interface at all, short of exactly grazing rays.
+        for i in 1..=10 {
+            assert!(dielectric(i as f64 / 10., 1.).abs() < 1e-12);
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{prelude::*};
+use crate::materials::materials::ScatterRecord;
+use crate::prelude::*;
 
 #[derive(Debug, Clone)]
 pub struct Isotropic {
@@ -10,19 +11,32 @@ impl Isotropic {
         Self {tex: Textures::solid_color(albedo)}
     }
 
-    // pub fn new(tex: Arc<Textures>) -> Self {
-    //     Self {tex}
-    // }
+    #[allow(dead_code)]
+    pub fn new(tex: Arc<Textures>) -> Self {
+        Self {tex}
+    }
 
     pub fn scatter(
         &self,
         ray: &Ray,
         hit_record: &HitRecord,
         rng: &mut ThreadRng,
-    ) -> Option<(Color3, Ray)> {
-        let scattered = Ray::new_time(hit_record.point, Vec3::random_unit(rng), ray.time);
-        let attenuation = self.tex.value(hit_record.u, hit_record.v, &hit_record.point);
-        Some((attenuation, scattered))
+    ) -> Option<ScatterRecord> {
+        let scattered = hit_record.spawn_ray(Vec3::random_unit(rng), ray.time);
+        let attenuation = self.tex.sample(hit_record);
+        Some(ScatterRecord {
+            attenuation,
+            scattered,
+            pdf: Some(self.pdf()),
+        })
+    }
+
+    pub fn eval(&self, hit_record: &HitRecord) -> Color3 {
+        self.tex.sample(hit_record) * self.pdf()
+    }
+
+    pub fn pdf(&self) -> f64 {
+        1. / (4. * PI)
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::materials::materials::ScatterRecord;
 use crate::prelude::*;
 
 /// Represents a Lambertian (diffuse) material.
@@ -36,25 +37,41 @@ impl Lambertian {
     ///
     /// # Returns
     ///
-    /// An optional tuple containing the attenuation color and the scattered ray.
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
     /// If `None` is returned, the ray is absorbed.
     pub(crate) fn scatter(
         &self,
         ray_in: &Ray,
         hit_record: &HitRecord,
         rng: &mut ThreadRng,
-    ) -> Option<(Color3, Ray)> {
+    ) -> Option<ScatterRecord> {
         let scatter_direction = {
-            let res = hit_record.normal + Vec3::random_unit(rng);
+            let res = hit_record.shading_normal + Vec3::random_unit(rng);
             if res.near_zero() {
-                hit_record.normal
+                hit_record.shading_normal
             } else {
                 res
             }
         };
 
-        let attenuation = self.texture.value(hit_record.u, hit_record.v, &hit_record.point);
-        let scattered = Ray::new_time(hit_record.point, scatter_direction, ray_in.time);
-        Some((attenuation, scattered))
+        let attenuation = self.texture.sample(hit_record);
+        let scattered = hit_record.spawn_ray(scatter_direction, ray_in.time);
+        Some(ScatterRecord {
+            attenuation,
+            scattered,
+            pdf: Some(self.pdf(hit_record, &scatter_direction)),
+        })
+    }
+
+    /// Evaluates the BRDF times the cosine term for light arriving from `direction`.
+    pub(crate) fn eval(&self, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        let albedo = self.texture.sample(hit_record);
+        albedo * self.pdf(hit_record, direction)
+    }
+
+    /// Returns the cosine-weighted pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        let cosine = hit_record.shading_normal.dot(&direction.normalize());
+        cosine.max(0.) / PI
     }
 }
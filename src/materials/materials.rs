@@ -1,10 +1,35 @@
-use crate::materials::dielectric::Dielectric;
+use crate::materials::dielectric::{Dielectric, Dispersion};
 use crate::materials::lambertian::Lambertian;
 use crate::materials::metal::Metal;
 use crate::materials::emmiter::Diffuse;
 use crate::materials::isotropic::Isotropic;
+use crate::materials::conductor::Conductor;
+use crate::materials::rough_dielectric::RoughDielectric;
+use crate::materials::principled::{Principled, PrincipledArgs};
+use crate::materials::mix::Mix;
+use crate::materials::coated::Coated;
+use crate::materials::bump::{Bump, BumpMap};
+use crate::materials::thin_film::ThinFilm;
+use crate::materials::subsurface::Subsurface;
+use crate::materials::phase::PhaseFunction;
+use crate::materials::volume::Volume;
 use crate::prelude::*;
 
+/// The result of sampling a material's scattering distribution.
+///
+/// # Fields
+/// - `attenuation`: The throughput weight of the scattered ray, i.e. the BSDF times the cosine
+///   term divided by the pdf of the sampled direction.
+/// - `scattered`: The scattered ray.
+/// - `pdf`: The solid-angle pdf of the sampled direction, or `None` if it came from a specular
+///   (delta) lobe that light sampling can never reach.
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterRecord {
+    pub attenuation: Color3,
+    pub scattered: Ray,
+    pub pdf: Option<f64>,
+}
+
 /// Represents the different types of materials that can be used in the ray tracer.
 /// Each material has its own scattering behavior.
 #[derive(Debug, Clone)]
@@ -18,7 +43,23 @@ pub enum Materials {
     /// Diffuse emitter
     Diffuse(Diffuse),
 
-    Isotropic(Isotropic)
+    Isotropic(Isotropic),
+    /// A rough conductor using the GGX microfacet model.
+    Conductor(Conductor),
+    /// A rough dielectric (frosted glass) using a microfacet model.
+    RoughDielectric(RoughDielectric),
+    /// A Disney-style principled material combining diffuse, metal, glass and coat lobes.
+    Principled(Principled),
+    /// A blend of two materials.
+    Mix(Mix),
+    /// A dielectric coat layered over another material.
+    Coated(Coated),
+    /// Another material shaded with a normal perturbed by a height or normal map.
+    Bump(Bump),
+    /// A translucent material with subsurface scattering.
+    Subsurface(Subsurface),
+    /// The scattering of a participating medium with a chosen phase function.
+    Volume(Volume),
 }
 
 impl Materials {
@@ -67,25 +108,452 @@ impl Materials {
         Arc::new(Self::Dielectric(Dielectric::new(refraction_index)))
     }
 
-    // pub fn emmiter(texture : Arc<Textures>) -> Arc<Self> {
-    //     Arc::new(Self::Diffuse(Diffuse::new(texture)))
-    // }
+    /// Creates a new Dielectric covered by a thin film, which makes its reflections iridescent.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction of the material under the film.
+    /// * `film` - The film covering the surface.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Dielectric material.
+    #[allow(dead_code)]
+    pub fn dielectric_thin_film(refraction_index: f64, film: ThinFilm) -> Arc<Self> {
+        Arc::new(Self::Dielectric(Dielectric::new(refraction_index).with_film(film)))
+    }
+
+    /// Creates a soap bubble: a film of soapy water with air on both sides.
+    ///
+    /// # Arguments
+    ///
+    /// * `thickness_map` - A texture in [0, 1] scaling the film between 0 and 1000nm, so
+    ///   swirling noise gives the familiar bands of color.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Dielectric material.
+    #[allow(dead_code)]
+    pub fn soap_bubble(thickness_map: Arc<Textures>) -> Arc<Self> {
+        Self::dielectric_thin_film(1.0, ThinFilm::textured(1.33, 1000., thickness_map))
+    }
+
+    /// Creates a new Dielectric whose index of refraction varies with wavelength.
+    ///
+    /// Dispersion only shows up when the camera renders in spectral mode; in RGB mode
+    /// the index at the d line (587.56nm) is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispersion` - The dispersion model and its coefficients.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Dielectric material.
+    #[allow(dead_code)]
+    pub fn dispersive_dielectric(dispersion: Dispersion) -> Arc<Self> {
+        Arc::new(Self::Dielectric(Dielectric::dispersive(dispersion)))
+    }
+
+    /// Creates Schott N-BK7 crown glass, the common optical glass.
+    #[allow(dead_code)]
+    pub fn bk7_glass() -> Arc<Self> {
+        Self::dispersive_dielectric(Dispersion::Sellmeier {
+            b: [1.03961212, 0.231792344, 1.01046945],
+            c: [0.00600069867, 0.0200179144, 103.560653],
+        })
+    }
+
+    /// Creates Schott SF11 dense flint glass, which disperses light strongly.
+    #[allow(dead_code)]
+    pub fn flint_glass() -> Arc<Self> {
+        Self::dispersive_dielectric(Dispersion::Sellmeier {
+            b: [1.73759695, 0.313747346, 1.89878101],
+            c: [0.013188707, 0.0623068142, 155.23629],
+        })
+    }
+
+    /// Creates fused silica (quartz glass).
+    #[allow(dead_code)]
+    pub fn fused_silica() -> Arc<Self> {
+        Self::dispersive_dielectric(Dispersion::Sellmeier {
+            b: [0.6961663, 0.4079426, 0.8974794],
+            c: [0.0046791483, 0.0135120631, 97.9340025],
+        })
+    }
+
+    /// Creates diamond, whose high index and dispersion produce its "fire".
+    #[allow(dead_code)]
+    pub fn diamond() -> Arc<Self> {
+        Self::dispersive_dielectric(Dispersion::Sellmeier {
+            b: [0.3306, 4.3356, 0.],
+            c: [0.030625, 0.011236, 0.],
+        })
+    }
+
+    /// Creates water, using Cauchy's equation.
+    #[allow(dead_code)]
+    pub fn water() -> Arc<Self> {
+        Self::dispersive_dielectric(Dispersion::Cauchy { a: 1.3199, b: 0.00653 })
+    }
+
+    /// Creates a new rough dielectric with textured roughness and Beer-Lambert absorption.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction of the material.
+    /// * `roughness` - The surface roughness in [0, 1], taken from the first channel of the texture.
+    /// * `absorption` - The absorption coefficient per unit distance travelled inside, per color channel.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a RoughDielectric material.
+    #[allow(dead_code)]
+    pub fn rough_dielectric(refraction_index: f64, roughness: Arc<Textures>, absorption: Color3) -> Arc<Self> {
+        Arc::new(Self::RoughDielectric(RoughDielectric::new(refraction_index, roughness, absorption)))
+    }
+
+    /// Creates a new clear, uniformly rough dielectric.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction of the material.
+    /// * `roughness` - The surface roughness in [0, 1].
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a RoughDielectric material.
+    #[allow(dead_code)]
+    pub fn frosted_glass(refraction_index: f64, roughness: f64) -> Arc<Self> {
+        Self::rough_dielectric(refraction_index, Textures::rgb(roughness, roughness, roughness), Color3::zero())
+    }
+
+    /// Creates a new GGX conductor from its complex index of refraction.
+    ///
+    /// # Arguments
+    ///
+    /// * `eta` - The real part of the index of refraction, per color channel.
+    /// * `k` - The absorption coefficient, per color channel.
+    /// * `roughness` - The surface roughness in [0, 1]; `0.0` is a perfect mirror.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Conductor material.
+    #[allow(dead_code)]
+    pub fn conductor(eta: Color3, k: Color3, roughness: f64) -> Arc<Self> {
+        Self::conductor_anisotropic(eta, k, roughness, roughness)
+    }
+
+    /// Creates a new anisotropic GGX conductor, e.g. for brushed metal.
+    ///
+    /// # Arguments
+    ///
+    /// * `eta` - The real part of the index of refraction, per color channel.
+    /// * `k` - The absorption coefficient, per color channel.
+    /// * `roughness_u` - The roughness along the surface tangent, in [0, 1].
+    /// * `roughness_v` - The roughness along the surface bitangent, in [0, 1].
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Conductor material.
+    #[allow(dead_code)]
+    pub fn conductor_anisotropic(eta: Color3, k: Color3, roughness_u: f64, roughness_v: f64) -> Arc<Self> {
+        Arc::new(Self::Conductor(Conductor::new(eta, k, roughness_u, roughness_v)))
+    }
+
+    /// Creates a new GGX conductor covered by a thin film, e.g. anodized titanium or heat-tinted steel.
+    ///
+    /// # Arguments
+    ///
+    /// * `eta` - The real part of the index of refraction, per color channel.
+    /// * `k` - The absorption coefficient, per color channel.
+    /// * `roughness` - The surface roughness in [0, 1]; `0.0` is a perfect mirror.
+    /// * `film` - The film covering the metal.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Conductor material.
+    #[allow(dead_code)]
+    pub fn conductor_thin_film(eta: Color3, k: Color3, roughness: f64, film: ThinFilm) -> Arc<Self> {
+        Arc::new(Self::Conductor(Conductor::new(eta, k, roughness, roughness).with_film(film)))
+    }
+
+    /// Creates a gold conductor with the given roughness.
+    #[allow(dead_code)]
+    pub fn gold(roughness: f64) -> Arc<Self> {
+        Self::conductor(Color3::new(0.143, 0.374, 1.442), Color3::new(3.983, 2.386, 1.603), roughness)
+    }
+
+    /// Creates a copper conductor with the given roughness.
+    #[allow(dead_code)]
+    pub fn copper(roughness: f64) -> Arc<Self> {
+        Self::conductor(Color3::new(0.200, 0.924, 1.102), Color3::new(3.912, 2.452, 2.142), roughness)
+    }
+
+    /// Creates an aluminium conductor with the given roughness.
+    #[allow(dead_code)]
+    pub fn aluminium(roughness: f64) -> Arc<Self> {
+        Self::conductor(Color3::new(1.657, 0.880, 0.521), Color3::new(9.224, 6.270, 4.837), roughness)
+    }
+
+    /// Creates a silver conductor with the given roughness.
+    #[allow(dead_code)]
+    pub fn silver(roughness: f64) -> Arc<Self> {
+        Self::conductor(Color3::new(0.155, 0.117, 0.138), Color3::new(4.828, 3.122, 2.147), roughness)
+    }
+
+    /// Creates a new principled material.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - A `PrincipledArgs` struct containing the textures driving each parameter.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Principled material.
+    #[allow(dead_code)]
+    pub fn principled(args: PrincipledArgs) -> Arc<Self> {
+        Arc::new(Self::Principled(Principled::new(args)))
+    }
+
+    /// Creates a constant blend of two materials.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The material used where the factor is `0.0`.
+    /// * `second` - The material used where the factor is `1.0`.
+    /// * `factor` - How much of `second` to use, in [0, 1].
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Mix material.
+    #[allow(dead_code)]
+    pub fn mix(first: Arc<Materials>, second: Arc<Materials>, factor: f64) -> Arc<Self> {
+        Self::mix_textured(first, second, Textures::rgb(factor, factor, factor))
+    }
+
+    /// Creates a blend of two materials driven by a texture, e.g. a dust or rust mask.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The material used where the factor is `0.0`.
+    /// * `second` - The material used where the factor is `1.0`.
+    /// * `factor` - How much of `second` to use, taken from the first channel of the texture.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Mix material.
+    #[allow(dead_code)]
+    pub fn mix_textured(first: Arc<Materials>, second: Arc<Materials>, factor: Arc<Textures>) -> Arc<Self> {
+        Arc::new(Self::Mix(Mix::new(first, second, factor)))
+    }
+
+    /// Adds bumps from a height map to a material, e.g. a noise texture for a rough plaster wall.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The material to add the bumps to.
+    /// * `height` - The height map, whose luminance is the height at each point.
+    /// * `scale` - The height, in world units, of a luminance of `1.0`.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Bump material.
+    pub fn bump_mapped(base: Arc<Materials>, height: Arc<Textures>, scale: f64) -> Arc<Self> {
+        Arc::new(Self::Bump(Bump::new(base, BumpMap::Height { tex: height, scale })))
+    }
+
+    /// Adds detail from a tangent-space normal map, such as one baked from a sculpt, to a material.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The material to add the detail to.
+    /// * `normal_map` - The normal map, with the usual encoding where `(0.5, 0.5, 1.0)` is the unperturbed normal.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Bump material.
+    pub fn normal_mapped(base: Arc<Materials>, normal_map: Arc<Textures>) -> Arc<Self> {
+        Arc::new(Self::Bump(Bump::new(base, BumpMap::Normal(normal_map))))
+    }
+
+    /// Creates a material with a dielectric coat, such as varnish or car paint clear coat, over a base.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The material underneath the coat.
+    /// * `refraction_index` - The index of refraction of the coat.
+    /// * `roughness` - The roughness of the coat in [0, 1]; `0.0` is perfectly smooth.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Coated material.
+    #[allow(dead_code)]
+    pub fn coated(base: Arc<Materials>, refraction_index: f64, roughness: f64) -> Arc<Self> {
+        Arc::new(Self::Coated(Coated::new(base, refraction_index, roughness)))
+    }
+
+    /// Creates a translucent material that scatters light beneath its surface.
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - The single-scattering albedo in [0, 1], per color channel.
+    /// * `mean_free_path` - The average distance light travels between interactions, in scene units, per color channel.
+    /// * `anisotropy` - The Henyey-Greenstein asymmetry parameter in (-1, 1); `0.0` scatters uniformly.
+    /// * `refraction_index` - The index of refraction of the surface.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Subsurface material.
+    #[allow(dead_code)]
+    pub fn subsurface(albedo: Color3, mean_free_path: Color3, anisotropy: f64, refraction_index: f64) -> Arc<Self> {
+        Arc::new(Self::Subsurface(Subsurface::new(albedo, mean_free_path, anisotropy, refraction_index)))
+    }
+
+    /// Creates a subsurface material from scattering and absorption coefficients per millimetre,
+    /// as measured by Jensen et al. (2001).
+    ///
+    /// # Arguments
+    ///
+    /// * `sigma_s` - The scattering coefficient per millimetre, per color channel.
+    /// * `sigma_a` - The absorption coefficient per millimetre, per color channel.
+    /// * `mm_per_unit` - How many millimetres one scene unit represents.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Subsurface material.
+    #[allow(dead_code)]
+    pub fn subsurface_measured(sigma_s: Color3, sigma_a: Color3, mm_per_unit: f64) -> Arc<Self> {
+        let sigma_t = sigma_s + sigma_a;
+        let albedo = Color3::new(sigma_s.x / sigma_t.x, sigma_s.y / sigma_t.y, sigma_s.z / sigma_t.z);
+        let mean_free_path = Color3::new(1. / sigma_t.x, 1. / sigma_t.y, 1. / sigma_t.z) / mm_per_unit;
+        Self::subsurface(albedo, mean_free_path, 0., 1.3)
+    }
+
+    /// Creates marble, given how many millimetres one scene unit represents.
+    #[allow(dead_code)]
+    pub fn marble(mm_per_unit: f64) -> Arc<Self> {
+        Self::subsurface_measured(Color3::new(2.19, 2.62, 3.00), Color3::new(0.0021, 0.0041, 0.0071), mm_per_unit)
+    }
+
+    /// Creates skin, given how many millimetres one scene unit represents.
+    #[allow(dead_code)]
+    pub fn skin(mm_per_unit: f64) -> Arc<Self> {
+        Self::subsurface_measured(Color3::new(0.74, 0.88, 1.01), Color3::new(0.032, 0.17, 0.48), mm_per_unit)
+    }
+
+    /// Creates whole milk, given how many millimetres one scene unit represents.
+    #[allow(dead_code)]
+    pub fn milk(mm_per_unit: f64) -> Arc<Self> {
+        Self::subsurface_measured(Color3::new(2.55, 3.21, 3.77), Color3::new(0.0011, 0.0024, 0.014), mm_per_unit)
+    }
+
+    /// Creates ketchup, given how many millimetres one scene unit represents.
+    #[allow(dead_code)]
+    pub fn ketchup(mm_per_unit: f64) -> Arc<Self> {
+        Self::subsurface_measured(Color3::new(0.18, 0.07, 0.03), Color3::new(0.061, 0.97, 1.45), mm_per_unit)
+    }
+
+    /// Creates a new two-sided diffuse emitter whose radiance comes from a texture, e.g. a screen or sign.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The emitted radiance.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Diffuse emitter.
+    #[allow(dead_code)]
+    pub fn emmiter(texture : Arc<Textures>) -> Arc<Self> {
+        Self::emmiter_with(texture, 1., true)
+    }
+
+    /// Creates a new diffuse emitter with a strength and a choice of emitting faces.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The emitted radiance, before scaling by `strength`.
+    /// * `strength` - The scale applied to the texture, so image textures can act as bright lights.
+    /// * `two_sided` - Whether the back face emits too; one-sided emitters only light the side
+    ///   their outward normal points to.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Diffuse emitter.
+    #[allow(dead_code)]
+    pub fn emmiter_with(texture : Arc<Textures>, strength: f64, two_sided: bool) -> Arc<Self> {
+        Arc::new(Self::Diffuse(Diffuse::new(texture, strength, two_sided)))
+    }
 
     pub fn emmiter_solid(color: Color3) -> Arc<Self> {
         Arc::new(Self::Diffuse(Diffuse::solid(color)))
     }
 
-    // pub fn isotropic(tex: Arc<Textures>) -> Arc<Self> {
-    //     Arc::new(Self::Isotropic(Isotropic::new(tex)))
-    // }
+    /// Creates a new isotropic phase function whose albedo comes from a texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `tex` - The albedo of the medium.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing an Isotropic material.
+    #[allow(dead_code)]
+    pub fn isotropic(tex: Arc<Textures>) -> Arc<Self> {
+        Arc::new(Self::Isotropic(Isotropic::new(tex)))
+    }
 
     pub fn isotropic_solid(albedo: Color3) -> Arc<Self> {
         Arc::new(Self::Isotropic(Isotropic::solid(albedo)))
     }
 
-    pub fn emitted(&self, u : f64, v : f64, p : &Point3) -> Color3 {
+    /// Creates the scattering behaviour of a medium with a chosen phase function.
+    ///
+    /// # Arguments
+    ///
+    /// * `tex` - The albedo of the medium.
+    /// * `phase` - The phase function, e.g. a forward-scattering Henyey-Greenstein lobe for haze.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Volume material.
+    pub fn volume(tex: Arc<Textures>, phase: PhaseFunction) -> Arc<Self> {
+        Arc::new(Self::Volume(Volume::new(tex, phase)))
+    }
+
+    /// Creates the scattering behaviour of a medium that also absorbs and emits light.
+    ///
+    /// # Arguments
+    ///
+    /// * `tex` - The color of scattered light.
+    /// * `phase` - The phase function of the medium.
+    /// * `albedo` - The fraction of interactions that scatter rather than absorb.
+    /// * `emission` - The radiance emitted at each interaction, already weighted by the fraction that absorb.
+    ///
+    /// # Returns
+    ///
+    /// A `Materials` enum variant containing a Volume material.
+    pub fn volume_with(tex: Arc<Textures>, phase: PhaseFunction, albedo: f64, emission: Color3) -> Arc<Self> {
+        Arc::new(Self::Volume(Volume::with_coefficients(tex, phase, albedo, emission)))
+    }
+
+    /// Returns the light the material emits at a hit.
+    ///
+    /// # Arguments
+    ///
+    /// * `hit_record` - Information about the hit point, including which face was hit.
+    ///
+    /// # Returns
+    ///
+    /// The emitted radiance as a `Color3`, zero for materials that do not emit.
+    pub fn emitted(&self, hit_record: &HitRecord) -> Color3 {
         match self {
-            Self::Diffuse(d) => d.emitted(u, v, p),
+            Self::Diffuse(d) => d.emitted(hit_record),
+            Self::Principled(mat) => mat.emitted(hit_record),
+            Self::Mix(mat) => mat.emitted(hit_record),
+            Self::Coated(mat) => mat.emitted(hit_record),
+            Self::Bump(mat) => mat.emitted(hit_record),
+            Self::Volume(mat) => mat.emitted(),
             _ => Color3::zero(),
         }
     }
@@ -100,20 +568,83 @@ impl Materials {
     ///
     /// # Returns
     ///
-    /// An optional tuple containing the attenuation color and the scattered ray.
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
     /// If `None` is returned, the ray is absorbed.
     pub fn scatter(
         &self,
         ray: &Ray,
         hit_record: &HitRecord,
         rng: &mut ThreadRng,
-    ) -> Option<(Color3, Ray)> {
+    ) -> Option<ScatterRecord> {
         match self {
             Self::Lambertian(l) => l.scatter(ray, hit_record, rng),
             Self::Dielectric(d) => d.scatter(ray, hit_record, rng),
             Self::Metal(m) => m.scatter(ray, hit_record, rng),
             Self::Isotropic(mat) => mat.scatter(ray, hit_record, rng),
+            Self::Conductor(c) => c.scatter(ray, hit_record, rng),
+            Self::RoughDielectric(d) => d.scatter(ray, hit_record, rng),
+            Self::Principled(mat) => mat.scatter(ray, hit_record, rng),
+            Self::Mix(mat) => mat.scatter(ray, hit_record, rng),
+            Self::Coated(mat) => mat.scatter(ray, hit_record, rng),
+            Self::Bump(mat) => mat.scatter(ray, hit_record, rng),
+            Self::Subsurface(mat) => mat.scatter(ray, hit_record, rng),
+            Self::Volume(mat) => mat.scatter(ray, hit_record, rng),
             _ => None,
         }
     }
+
+    /// Evaluates the BSDF times the cosine term for light arriving from `direction`.
+    ///
+    /// Used for light sampling, so specular materials (which can never be hit by a sampled
+    /// direction) return zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point.
+    /// * `direction` - The direction light arrives from.
+    ///
+    /// # Returns
+    ///
+    /// The BSDF value multiplied by the cosine term as a `Color3`.
+    pub fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        match self {
+            Self::Lambertian(l) => l.eval(hit_record, direction),
+            Self::Isotropic(mat) => mat.eval(hit_record),
+            Self::Conductor(c) => c.eval(ray, hit_record, direction),
+            Self::RoughDielectric(d) => d.eval(ray, hit_record, direction),
+            Self::Principled(mat) => mat.eval(ray, hit_record, direction),
+            Self::Mix(mat) => mat.eval(ray, hit_record, direction),
+            Self::Coated(mat) => mat.eval(ray, hit_record, direction),
+            Self::Bump(mat) => mat.eval(ray, hit_record, direction),
+            Self::Volume(mat) => mat.eval(ray, hit_record, direction),
+            _ => Color3::zero(),
+        }
+    }
+
+    /// Returns the solid-angle pdf with which `scatter` would pick `direction`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point.
+    /// * `direction` - The candidate scattered direction.
+    ///
+    /// # Returns
+    ///
+    /// The pdf, or `0.0` for specular materials.
+    pub fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        match self {
+            Self::Lambertian(l) => l.pdf(hit_record, direction),
+            Self::Isotropic(mat) => mat.pdf(),
+            Self::Conductor(c) => c.pdf(ray, hit_record, direction),
+            Self::RoughDielectric(d) => d.pdf(ray, hit_record, direction),
+            Self::Principled(mat) => mat.pdf(ray, hit_record, direction),
+            Self::Mix(mat) => mat.pdf(ray, hit_record, direction),
+            Self::Coated(mat) => mat.pdf(ray, hit_record, direction),
+            Self::Bump(mat) => mat.pdf(ray, hit_record, direction),
+            Self::Volume(mat) => mat.pdf(ray, direction),
+            _ => 0.,
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::materials::materials::ScatterRecord;
 use crate::prelude::*;
 
 /// Represents a metallic material.
@@ -35,20 +36,24 @@ impl Metal {
     ///
     /// # Returns
     ///
-    /// An optional tuple containing the attenuation color and the scattered ray.
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
     /// If `None` is returned, the ray is absorbed.
     pub(crate) fn scatter(
         &self,
         ray_in: &Ray,
         hit_record: &HitRecord,
         rng: &mut ThreadRng,
-    ) -> Option<(Color3, Ray)> {
-        let reflected = ray_in.direction.reflect(&hit_record.normal).normalize()
+    ) -> Option<ScatterRecord> {
+        let reflected = ray_in.direction.reflect(&hit_record.shading_normal).normalize()
             + self.fuzz * Vec3::random_unit(rng);
-        let scattered = Ray::new_time(hit_record.point, reflected, ray_in.time);
+        let scattered = hit_record.spawn_ray(reflected, ray_in.time);
 
-        if scattered.direction.dot(&hit_record.normal) > 0.0 {
-            Some((self.albedo, scattered))
+        if scattered.direction.dot(&hit_record.shading_normal) > 0.0 {
+            Some(ScatterRecord {
+                attenuation: self.albedo,
+                scattered,
+                pdf: None,
+            })
         } else {
             None
         }
@@ -0,0 +1,147 @@
+use crate::prelude::*;
+
+/// GGX `alpha` below which a microfacet lobe is treated as a perfect (delta) mirror.
+const SPECULAR_ALPHA: f64 = 1e-3;
+/// The smallest `alpha` used, so the distribution never degenerates into a division by zero.
+const MIN_ALPHA: f64 = 1e-4;
+
+/// The GGX (Trowbridge-Reitz) microfacet distribution, with Smith's height-correlated masking.
+///
+/// All directions are expressed in the local shading frame, where the surface normal is `+z`.
+///
+/// # Fields
+/// - `alpha_x`: The roughness along the local `x` axis.
+/// - `alpha_y`: The roughness along the local `y` axis.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Ggx {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl Ggx {
+    /// Creates a distribution from perceptual roughness values in [0, 1].
+    ///
+    /// Roughness is squared to get `alpha`, which makes the parameter feel linear.
+    ///
+    /// # Arguments
+    ///
+    /// * `roughness_x` - The roughness along the local `x` axis.
+    /// * `roughness_y` - The roughness along the local `y` axis.
+    ///
+    /// # Returns
+    ///
+    /// A new `Ggx` distribution.
+    pub(crate) fn new(roughness_x: f64, roughness_y: f64) -> Self {
+        Self {
+            alpha_x: (roughness_x * roughness_x).max(MIN_ALPHA),
+            alpha_y: (roughness_y * roughness_y).max(MIN_ALPHA),
+        }
+    }
+
+    /// Returns whether the distribution is smooth enough to be treated as a perfect mirror.
+    pub(crate) fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) <= SPECULAR_ALPHA
+    }
+
+    /// Evaluates the normal distribution function for a microfacet normal `wm`.
+    pub(crate) fn d(&self, wm: &Vec3) -> f64 {
+        if wm.z <= 0. {
+            return 0.;
+        }
+        let x = wm.x / self.alpha_x;
+        let y = wm.y / self.alpha_y;
+        let denom = x * x + y * y + wm.z * wm.z;
+        1. / (PI * self.alpha_x * self.alpha_y * denom * denom)
+    }
+
+    /// Evaluates Smith's auxiliary function Λ for direction `w`.
+    fn lambda(&self, w: &Vec3) -> f64 {
+        if w.z == 0. {
+            return INF;
+        }
+        let ax = self.alpha_x * w.x;
+        let ay = self.alpha_y * w.y;
+        let tan2 = (ax * ax + ay * ay) / (w.z * w.z);
+        0.5 * (-1. + (1. + tan2).sqrt())
+    }
+
+    /// The fraction of microfacets visible from direction `w`.
+    pub(crate) fn g1(&self, w: &Vec3) -> f64 {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// The fraction of microfacets visible from both `wo` and `wi`.
+    pub(crate) fn g2(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1. / (1. + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// The distribution of normals visible from `wo`, as a density over microfacet normals.
+    pub(crate) fn visible_d(&self, wo: &Vec3, wm: &Vec3) -> f64 {
+        let cos_o = wo.z.abs();
+        if cos_o == 0. {
+            return 0.;
+        }
+        self.g1(wo) / cos_o * self.d(wm) * wo.dot(wm).abs()
+    }
+
+    /// Samples a microfacet normal from the distribution of normals visible from `wo` (Heitz 2018).
+    ///
+    /// # Arguments
+    ///
+    /// * `wo` - The outgoing direction in the local frame, in the upper hemisphere.
+    /// * `rng` - A random number generator.
+    ///
+    /// # Returns
+    ///
+    /// The sampled microfacet normal in the local frame.
+    pub(crate) fn sample_visible_normal(&self, wo: &Vec3, rng: &mut ThreadRng) -> Vec3 {
+        // Stretch the view direction so the distribution becomes a hemisphere.
+        let vh = Vec3::new(self.alpha_x * wo.x, self.alpha_y * wo.y, wo.z).normalize();
+
+        let len_sq = vh.x * vh.x + vh.y * vh.y;
+        let t1 = if len_sq > 0. {
+            Vec3::new(-vh.y, vh.x, 0.) / len_sq.sqrt()
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let t2 = vh.cross(&t1);
+
+        // Sample a point on the projected disk, warped towards the visible half.
+        let r = gen_01(rng).sqrt();
+        let phi = 2. * PI * gen_01(rng);
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1. + vh.z);
+        let p2 = (1. - s) * (1. - p1 * p1).sqrt() + s * r * phi.sin();
+
+        let nh = p1 * t1 + p2 * t2 + (1. - p1 * p1 - p2 * p2).max(0.).sqrt() * vh;
+
+        // Unstretch back to the ellipsoid configuration.
+        Vec3::new(self.alpha_x * nh.x, self.alpha_y * nh.y, nh.z.max(1e-6)).normalize()
+    }
+}
+
+/// Reflects `wo` about the microfacet normal `wm`.
+pub(crate) fn reflect(wo: &Vec3, wm: &Vec3) -> Vec3 {
+    2. * wo.dot(wm) * *wm - *wo
+}
+
+/// Refracts `wo` through a microfacet with normal `wm`.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, on the same side as `wm`.
+/// * `wm` - The microfacet normal.
+/// * `eta` - The relative index of refraction, transmitted side over incident side.
+///
+/// # Returns
+///
+/// The refracted direction, or `None` under total internal reflection.
+pub(crate) fn refract(wo: &Vec3, wm: &Vec3, eta: f64) -> Option<Vec3> {
+    let cos_theta_i = wo.dot(wm);
+    let sin2_theta_t = (1. - cos_theta_i * cos_theta_i).max(0.) / (eta * eta);
+    if sin2_theta_t >= 1. {
+        return None;
+    }
+    let cos_theta_t = (1. - sin2_theta_t).sqrt();
+    Some(-*wo / eta + (cos_theta_i / eta - cos_theta_t) * *wm)
+}
@@ -0,0 +1,104 @@
+use crate::materials::materials::ScatterRecord;
+use crate::prelude::*;
+
+/// Blends two materials, e.g. dust over a metal, by a constant or textured factor.
+///
+/// The BSDF is `(1 - t) * first + t * second`. Scattering picks one of the two materials
+/// with probability given by the blend factor.
+#[derive(Debug, Clone)]
+pub(crate) struct Mix {
+    /// The material used where the factor is `0.0`.
+    first: Arc<Materials>,
+    /// The material used where the factor is `1.0`.
+    second: Arc<Materials>,
+    /// The blend factor in [0, 1], read from the first channel of the texture.
+    factor: Arc<Textures>,
+}
+
+impl Mix {
+    /// Creates a new `Mix` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The material used where the factor is `0.0`.
+    /// * `second` - The material used where the factor is `1.0`.
+    /// * `factor` - The blend factor in [0, 1], taken from the first channel of the texture.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Mix`.
+    pub(crate) fn new(first: Arc<Materials>, second: Arc<Materials>, factor: Arc<Textures>) -> Self {
+        Self {
+            first,
+            second,
+            factor,
+        }
+    }
+
+    /// Returns the blend factor at a hit.
+    fn factor(&self, hit_record: &HitRecord) -> f64 {
+        self.factor.sample(hit_record).x.clamp(0., 1.)
+    }
+
+    /// Computes how a ray scatters when it hits the mix.
+    ///
+    /// Specular samples keep the weight of the material that produced them, since the
+    /// probability of choosing it cancels its blend weight. Other samples are reweighted
+    /// by the blended BSDF and pdf, as either material could have produced them.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point.
+    /// * `rng` - A random number generator used for choosing a material and sampling it.
+    ///
+    /// # Returns
+    ///
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
+    /// If `None` is returned, the ray is absorbed.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        let t = self.factor(hit_record);
+        let chosen = if gen_01(rng) < t {
+            &self.second
+        } else {
+            &self.first
+        };
+
+        let mut srec = chosen.scatter(ray_in, hit_record, rng)?;
+        if srec.pdf.is_some() {
+            let direction = srec.scattered.direction;
+            let pdf = self.pdf(ray_in, hit_record, &direction);
+            if pdf <= 0. {
+                return None;
+            }
+            srec.attenuation = self.eval(ray_in, hit_record, &direction) / pdf;
+            srec.pdf = Some(pdf);
+        }
+        Some(srec)
+    }
+
+    /// Evaluates the blended BSDF times the cosine term for light arriving from `direction`.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        let t = self.factor(hit_record);
+        self.first.eval(ray_in, hit_record, direction) * (1. - t)
+            + self.second.eval(ray_in, hit_record, direction) * t
+    }
+
+    /// Returns the pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        let t = self.factor(hit_record);
+        (1. - t) * self.first.pdf(ray_in, hit_record, direction)
+            + t * self.second.pdf(ray_in, hit_record, direction)
+    }
+
+    /// Returns the blended light emitted by the two materials.
+    pub(crate) fn emitted(&self, hit_record: &HitRecord) -> Color3 {
+        let t = self.factor(hit_record);
+        self.first.emitted(hit_record) * (1. - t) + self.second.emitted(hit_record) * t
+    }
+}
@@ -3,6 +3,19 @@ mod lambertian;
 mod metal;
 mod emmiter;
 mod isotropic;
+mod conductor;
+mod fresnel;
+mod microfacet;
+mod rough_dielectric;
+pub mod principled;
+mod mix;
+mod coated;
+mod bump;
+pub mod thin_film;
+pub mod phase;
+mod subsurface;
+mod volume;
 
+#[allow(clippy::module_inception)]
 pub mod materials;
 
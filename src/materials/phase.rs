@@ -0,0 +1,138 @@
+use crate::prelude::*;
+
+/// Describes how a participating medium distributes scattered light over directions.
+///
+/// Angles are measured between the direction the light was travelling and the direction
+/// it scatters to, so positive asymmetry favours scattering forwards.
+///
+/// # Variants
+/// - `Isotropic`: Scatters uniformly over the sphere.
+/// - `HenyeyGreenstein`: A single lobe with asymmetry `g` in (-1, 1); positive values
+///   scatter forwards, like haze, negative values backwards.
+/// - `TwoLobe`: A blend of a forward lobe `g1` and a backward lobe `g2`, where `weight`
+///   is the share of the first, like the silver lining and darker core of clouds.
+#[derive(Debug, Clone, Copy)]
+pub enum PhaseFunction {
+    Isotropic,
+    HenyeyGreenstein { g: f64 },
+    TwoLobe { g1: f64, g2: f64, weight: f64 },
+}
+
+impl PhaseFunction {
+    /// Creates a single-lobe Henyey-Greenstein phase function.
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - The asymmetry parameter, clamped to (-1, 1).
+    ///
+    /// # Returns
+    ///
+    /// A new `PhaseFunction`.
+    #[allow(dead_code)]
+    pub fn henyey_greenstein(g: f64) -> Self {
+        Self::HenyeyGreenstein { g: g.clamp(-0.99, 0.99) }
+    }
+
+    /// Creates a blend of two Henyey-Greenstein lobes.
+    ///
+    /// # Arguments
+    ///
+    /// * `g1` - The asymmetry parameter of the first lobe, clamped to (-1, 1).
+    /// * `g2` - The asymmetry parameter of the second lobe, clamped to (-1, 1).
+    /// * `weight` - The share of the first lobe in [0, 1].
+    ///
+    /// # Returns
+    ///
+    /// A new `PhaseFunction`.
+    #[allow(dead_code)]
+    pub fn two_lobe(g1: f64, g2: f64, weight: f64) -> Self {
+        Self::TwoLobe {
+            g1: g1.clamp(-0.99, 0.99),
+            g2: g2.clamp(-0.99, 0.99),
+            weight: weight.clamp(0., 1.),
+        }
+    }
+
+    /// Evaluates the phase function.
+    ///
+    /// # Arguments
+    ///
+    /// * `incoming` - The direction the light was travelling, normalized.
+    /// * `scattered` - The direction it scatters to, normalized.
+    ///
+    /// # Returns
+    ///
+    /// The density per steradian, which is also the pdf of `sample`.
+    pub fn eval(&self, incoming: &Vec3, scattered: &Vec3) -> f64 {
+        let cos_theta = incoming.dot(scattered);
+        match *self {
+            Self::Isotropic => 1. / (4. * PI),
+            Self::HenyeyGreenstein { g } => henyey_greenstein(cos_theta, g),
+            Self::TwoLobe { g1, g2, weight } => {
+                weight * henyey_greenstein(cos_theta, g1) + (1. - weight) * henyey_greenstein(cos_theta, g2)
+            }
+        }
+    }
+
+    /// Samples a scattered direction proportionally to the phase function.
+    ///
+    /// # Arguments
+    ///
+    /// * `incoming` - The direction the light was travelling, normalized.
+    /// * `rng` - A random number generator.
+    ///
+    /// # Returns
+    ///
+    /// The scattered direction, normalized.
+    pub fn sample(&self, incoming: &Vec3, rng: &mut ThreadRng) -> Vec3 {
+        match *self {
+            Self::Isotropic => Vec3::random_unit(rng),
+            Self::HenyeyGreenstein { g } => sample_henyey_greenstein(incoming, g, rng),
+            Self::TwoLobe { g1, g2, weight } => {
+                let g = if gen_01(rng) < weight { g1 } else { g2 };
+                sample_henyey_greenstein(incoming, g, rng)
+            }
+        }
+    }
+}
+
+/// Evaluates the Henyey-Greenstein phase function.
+///
+/// # Arguments
+///
+/// * `cos_theta` - The cosine of the angle between the incoming and scattered directions.
+/// * `g` - The asymmetry parameter in (-1, 1).
+///
+/// # Returns
+///
+/// The density per steradian.
+pub(crate) fn henyey_greenstein(cos_theta: f64, g: f64) -> f64 {
+    let denom = 1. + g * g - 2. * g * cos_theta;
+    (1. - g * g) / (4. * PI * denom * denom.max(1e-12).sqrt())
+}
+
+/// Samples a scattered direction from the Henyey-Greenstein phase function.
+///
+/// # Arguments
+///
+/// * `direction` - The propagation direction of the incoming light, normalized.
+/// * `g` - The asymmetry parameter in (-1, 1).
+/// * `rng` - A random number generator.
+///
+/// # Returns
+///
+/// The scattered direction, distributed according to the phase function.
+pub(crate) fn sample_henyey_greenstein(direction: &Vec3, g: f64, rng: &mut ThreadRng) -> Vec3 {
+    let u = gen_01(rng);
+    let cos_theta = if g.abs() < 1e-3 {
+        1. - 2. * u
+    } else {
+        let sqr = (1. - g * g) / (1. + g - 2. * g * u);
+        ((1. + g * g - sqr * sqr) / (2. * g)).clamp(-1., 1.)
+    };
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = 2. * PI * gen_01(rng);
+
+    let frame = Onb::new(direction);
+    frame.transform(&Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta))
+}
@@ -0,0 +1,378 @@
+use crate::materials::fresnel;
+use crate::materials::materials::ScatterRecord;
+use crate::materials::microfacet::{self, Ggx};
+use crate::prelude::*;
+
+/// Contains the parameters of a `Principled` material.
+///
+/// Every parameter except `ior` and `emission_strength` is a texture, so it can vary over
+/// the surface. Scalar parameters are read from the first channel of their texture and
+/// are expected to lie in [0, 1].
+pub struct PrincipledArgs {
+    /// The diffuse albedo, or the reflectance at normal incidence for metals.
+    pub base_color: Arc<Textures>,
+    /// Blends between a dielectric (`0.0`) and a metal (`1.0`).
+    pub metallic: Arc<Textures>,
+    /// The perceptual roughness of the specular and transmission lobes.
+    pub roughness: Arc<Textures>,
+    /// The strength of the dielectric specular highlight; `0.5` is a reflectance of 4%.
+    pub specular: Arc<Textures>,
+    /// The strength of the soft grazing-angle sheen used for cloth.
+    pub sheen: Arc<Textures>,
+    /// The strength of a second, colorless specular layer on top.
+    pub clearcoat: Arc<Textures>,
+    /// The perceptual roughness of the clearcoat layer.
+    pub clearcoat_roughness: Arc<Textures>,
+    /// Blends between an opaque (`0.0`) and a fully transmissive, glass-like (`1.0`) dielectric.
+    pub transmission: Arc<Textures>,
+    /// The index of refraction used by the transmission lobe.
+    pub ior: f64,
+    /// The emitted radiance, before scaling by `emission_strength`.
+    pub emission: Arc<Textures>,
+    /// The scale applied to `emission`.
+    pub emission_strength: f64,
+}
+
+impl Default for PrincipledArgs {
+    /// A grey, moderately rough dielectric that does not emit light.
+    fn default() -> Self {
+        Self {
+            base_color: Textures::rgb(0.8, 0.8, 0.8),
+            metallic: Textures::rgb(0., 0., 0.),
+            roughness: Textures::rgb(0.5, 0.5, 0.5),
+            specular: Textures::rgb(0.5, 0.5, 0.5),
+            sheen: Textures::rgb(0., 0., 0.),
+            clearcoat: Textures::rgb(0., 0., 0.),
+            clearcoat_roughness: Textures::rgb(0.03, 0.03, 0.03),
+            transmission: Textures::rgb(0., 0., 0.),
+            ior: 1.5,
+            emission: Textures::rgb(0., 0., 0.),
+            emission_strength: 1.,
+        }
+    }
+}
+
+/// A physically based "uber" material in the style of the Disney principled BSDF.
+///
+/// The BSDF is a weighted sum of a diffuse base with sheen, a GGX specular lobe whose
+/// Fresnel blends from dielectric to metal, a rough glass lobe and a clearcoat lobe.
+/// Sampling picks one lobe in proportion to its expected contribution and weights the
+/// result with the pdf of the whole mixture.
+#[derive(Debug, Clone)]
+pub(crate) struct Principled {
+    base_color: Arc<Textures>,
+    metallic: Arc<Textures>,
+    roughness: Arc<Textures>,
+    specular: Arc<Textures>,
+    sheen: Arc<Textures>,
+    clearcoat: Arc<Textures>,
+    clearcoat_roughness: Arc<Textures>,
+    transmission: Arc<Textures>,
+    ior: f64,
+    emission: Arc<Textures>,
+    emission_strength: f64,
+}
+
+/// The parameters of a `Principled` material looked up at a single hit, in the local shading frame.
+struct Lobes {
+    frame: Onb,
+    wo: Vec3,
+    /// The relative index of refraction across the surface, as seen from `wo`.
+    eta: f64,
+    base_color: Color3,
+    sheen: f64,
+    /// The normal-incidence reflectance of the specular lobe.
+    specular_f0: Color3,
+    /// The normal-incidence reflectance of the dielectric part of the specular lobe.
+    dielectric_f0: f64,
+    specular: Ggx,
+    clearcoat: Ggx,
+    /// The weights of the opaque dielectric, glass and clearcoat parts of the BSDF.
+    diffuse_weight: f64,
+    glass_weight: f64,
+    clearcoat_weight: f64,
+    /// The probabilities of sampling the diffuse, specular, glass and clearcoat lobes.
+    probabilities: [f64; 4],
+}
+
+/// The probability slots of the individual lobes.
+const DIFFUSE: usize = 0;
+const SPECULAR: usize = 1;
+const GLASS: usize = 2;
+const CLEARCOAT: usize = 3;
+
+/// Reads the first channel of a texture at a hit, clamped to [0, 1].
+fn scalar(texture: &Textures, hit_record: &HitRecord) -> f64 {
+    texture.sample(hit_record).x.clamp(0., 1.)
+}
+
+impl Principled {
+    /// Creates a new `Principled` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - A `PrincipledArgs` struct containing the material parameters.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Principled`.
+    pub(crate) fn new(args: PrincipledArgs) -> Self {
+        Self {
+            base_color: args.base_color,
+            metallic: args.metallic,
+            roughness: args.roughness,
+            specular: args.specular,
+            sheen: args.sheen,
+            clearcoat: args.clearcoat,
+            clearcoat_roughness: args.clearcoat_roughness,
+            transmission: args.transmission,
+            ior: args.ior,
+            emission: args.emission,
+            emission_strength: args.emission_strength,
+        }
+    }
+
+    /// Looks up the material parameters at a hit and derives the lobe weights and sampling probabilities.
+    fn lobes(&self, ray_in: &Ray, hit_record: &HitRecord) -> Lobes {
+        let frame = Onb::new(&hit_record.shading_normal);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        let eta = if hit_record.front_face {
+            self.ior
+        } else {
+            1. / self.ior
+        };
+
+        let base_color = self.base_color.sample(hit_record);
+        let metallic = scalar(&self.metallic, hit_record);
+        let roughness = scalar(&self.roughness, hit_record);
+        let transmission = scalar(&self.transmission, hit_record);
+        let clearcoat = scalar(&self.clearcoat, hit_record);
+        let clearcoat_roughness = scalar(&self.clearcoat_roughness, hit_record);
+
+        let dielectric_f0 = 0.08 * scalar(&self.specular, hit_record);
+        let specular_f0 = Color3::new(dielectric_f0, dielectric_f0, dielectric_f0) * (1. - metallic)
+            + base_color * metallic;
+
+        let diffuse_weight = (1. - metallic) * (1. - transmission);
+        let glass_weight = (1. - metallic) * transmission;
+        let clearcoat_weight = 0.25 * clearcoat;
+
+        let fresnel_o = fresnel::schlick(wo.z, &Color3::new(dielectric_f0, dielectric_f0, dielectric_f0)).x;
+        let mut probabilities = [
+            diffuse_weight * (1. - fresnel_o),
+            metallic + diffuse_weight * fresnel_o,
+            glass_weight,
+            clearcoat_weight,
+        ];
+        let total: f64 = probabilities.iter().sum();
+        if total > 0. {
+            probabilities.iter_mut().for_each(|p| *p /= total);
+        }
+
+        Lobes {
+            frame,
+            wo,
+            eta,
+            base_color,
+            sheen: scalar(&self.sheen, hit_record),
+            specular_f0,
+            dielectric_f0,
+            specular: Ggx::new(roughness, roughness),
+            clearcoat: Ggx::new(clearcoat_roughness, clearcoat_roughness),
+            diffuse_weight,
+            glass_weight,
+            clearcoat_weight,
+            probabilities,
+        }
+    }
+
+    /// The Schlick reflectance of the clearcoat layer, which has an index of refraction of 1.5.
+    fn clearcoat_fresnel(cos_theta: f64) -> f64 {
+        fresnel::schlick(cos_theta, &Color3::new(0.04, 0.04, 0.04)).x
+    }
+
+    /// Returns the generalized half vector for a pair of directions, facing `+z`.
+    fn half_vector(wo: &Vec3, wi: &Vec3, eta: f64) -> Vec3 {
+        let wm = if wi.z > 0. { *wo + *wi } else { *wo + *wi * eta };
+        let wm = wm.normalize();
+        if wm.z < 0. {
+            -wm
+        } else {
+            wm
+        }
+    }
+
+    /// Evaluates the full BSDF times the cosine term in the local frame.
+    fn eval_local(lobes: &Lobes, wi: &Vec3) -> Color3 {
+        let wo = lobes.wo;
+        if wo.z <= 0. || wi.z == 0. {
+            return Color3::zero();
+        }
+        let wm = Self::half_vector(&wo, wi, lobes.eta);
+        if wm.dot(wi) * wi.z < 0. || wm.dot(&wo) <= 0. {
+            return Color3::zero();
+        }
+
+        let mut value = Color3::zero();
+        if wi.z > 0. {
+            if lobes.diffuse_weight > 0. {
+                // The diffuse base only receives the light the specular layer lets through.
+                let dielectric_f0 = Color3::new(lobes.dielectric_f0, lobes.dielectric_f0, lobes.dielectric_f0);
+                let transmitted = 1. - fresnel::schlick(wo.z, &dielectric_f0).x;
+                let diffuse = lobes.base_color * (transmitted / PI);
+                let sheen = (1. - wi.dot(&wm)).powi(5) * lobes.sheen;
+                value = value
+                    + (diffuse + Color3::new(sheen, sheen, sheen)) * (lobes.diffuse_weight * wi.z);
+            }
+
+            let d = lobes.specular.d(&wm);
+            let g = lobes.specular.g2(&wo, wi);
+            let specular = fresnel::schlick(wo.dot(&wm), &lobes.specular_f0) * (d * g / (4. * wo.z));
+            value = value + specular * (1. - lobes.glass_weight);
+
+            if lobes.glass_weight > 0. {
+                let reflectance = fresnel::dielectric(wo.dot(&wm), lobes.eta);
+                let glass = d * g * reflectance / (4. * wo.z);
+                value = value + Color3::new(glass, glass, glass) * lobes.glass_weight;
+            }
+
+            if lobes.clearcoat_weight > 0. {
+                let d = lobes.clearcoat.d(&wm);
+                let g = lobes.clearcoat.g2(&wo, wi);
+                let reflectance = Self::clearcoat_fresnel(wo.dot(&wm));
+                let clearcoat = d * g * reflectance / (4. * wo.z);
+                // Light reflected by the coat never reaches the layers below it.
+                value = value * (1. - lobes.clearcoat_weight * Self::clearcoat_fresnel(wo.z))
+                    + Color3::new(clearcoat, clearcoat, clearcoat) * lobes.clearcoat_weight;
+            }
+        } else if lobes.glass_weight > 0. {
+            let reflectance = fresnel::dielectric(wo.dot(&wm), lobes.eta);
+            let d = lobes.specular.d(&wm);
+            let g = lobes.specular.g2(&wo, wi);
+            let denom = wi.dot(&wm) + wo.dot(&wm) / lobes.eta;
+            let transmitted =
+                (1. - reflectance) * d * g * (wi.dot(&wm) * wo.dot(&wm)).abs() / (wo.z * denom * denom);
+            // The tint is applied once per interface, so light passing through twice gets the base color.
+            let tint = Color3::new(
+                lobes.base_color.x.sqrt(),
+                lobes.base_color.y.sqrt(),
+                lobes.base_color.z.sqrt(),
+            );
+            value = tint * (transmitted * lobes.glass_weight);
+        }
+        value
+    }
+
+    /// Returns the pdf of the lobe mixture for a direction in the local frame.
+    fn pdf_local(lobes: &Lobes, wi: &Vec3) -> f64 {
+        let wo = lobes.wo;
+        if wo.z <= 0. || wi.z == 0. {
+            return 0.;
+        }
+        let wm = Self::half_vector(&wo, wi, lobes.eta);
+        if wm.dot(wi) * wi.z < 0. || wm.dot(&wo) <= 0. {
+            return 0.;
+        }
+
+        let p = &lobes.probabilities;
+        let mut pdf = 0.;
+        let visible = lobes.specular.visible_d(&wo, &wm);
+        if wi.z > 0. {
+            pdf += p[DIFFUSE] * wi.z / PI;
+            pdf += p[SPECULAR] * visible / (4. * wo.dot(&wm));
+            pdf += p[CLEARCOAT] * lobes.clearcoat.visible_d(&wo, &wm) / (4. * wo.dot(&wm));
+        }
+        if p[GLASS] > 0. {
+            let reflectance = fresnel::dielectric(wo.dot(&wm), lobes.eta);
+            pdf += p[GLASS]
+                * if wi.z > 0. {
+                    visible / (4. * wo.dot(&wm)) * reflectance
+                } else {
+                    let denom = wi.dot(&wm) + wo.dot(&wm) / lobes.eta;
+                    visible * wi.dot(&wm).abs() / (denom * denom) * (1. - reflectance)
+                };
+        }
+        pdf
+    }
+
+    /// Computes how a ray scatters when it hits the principled material.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point, including the normal and whether the hit was on the front face.
+    /// * `rng` - A random number generator used for choosing a lobe and sampling it.
+    ///
+    /// # Returns
+    ///
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
+    /// If `None` is returned, the ray is absorbed.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        let lobes = self.lobes(ray_in, hit_record);
+        let wo = lobes.wo;
+        if wo.z <= 0. {
+            return None;
+        }
+
+        let choice = gen_01(rng);
+        let p = &lobes.probabilities;
+        let wi = if choice < p[DIFFUSE] {
+            let direction = Vec3::new(0., 0., 1.) + Vec3::random_unit(rng);
+            if direction.near_zero() {
+                Vec3::new(0., 0., 1.)
+            } else {
+                direction.normalize()
+            }
+        } else if choice < p[DIFFUSE] + p[SPECULAR] {
+            let wm = lobes.specular.sample_visible_normal(&wo, rng);
+            microfacet::reflect(&wo, &wm)
+        } else if choice < p[DIFFUSE] + p[SPECULAR] + p[GLASS] {
+            let wm = lobes.specular.sample_visible_normal(&wo, rng);
+            let reflectance = fresnel::dielectric(wo.dot(&wm), lobes.eta);
+            if gen_01(rng) < reflectance {
+                microfacet::reflect(&wo, &wm)
+            } else {
+                microfacet::refract(&wo, &wm, lobes.eta)?
+            }
+        } else {
+            let wm = lobes.clearcoat.sample_visible_normal(&wo, rng);
+            microfacet::reflect(&wo, &wm)
+        };
+
+        let pdf = Self::pdf_local(&lobes, &wi);
+        if pdf <= 0. {
+            return None;
+        }
+        let attenuation = Self::eval_local(&lobes, &wi) / pdf;
+
+        Some(ScatterRecord {
+            attenuation,
+            scattered: hit_record.spawn_ray(lobes.frame.transform(&wi), ray_in.time),
+            pdf: Some(pdf),
+        })
+    }
+
+    /// Evaluates the BSDF times the cosine term for light arriving from `direction`.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        let lobes = self.lobes(ray_in, hit_record);
+        let wi = lobes.frame.inverse_transform(&direction.normalize());
+        Self::eval_local(&lobes, &wi)
+    }
+
+    /// Returns the pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        let lobes = self.lobes(ray_in, hit_record);
+        let wi = lobes.frame.inverse_transform(&direction.normalize());
+        Self::pdf_local(&lobes, &wi)
+    }
+
+    /// Returns the light emitted at a point on the surface.
+    pub(crate) fn emitted(&self, hit_record: &HitRecord) -> Color3 {
+        self.emission.sample(hit_record) * self.emission_strength
+    }
+}
@@ -0,0 +1,218 @@
+use crate::materials::fresnel;
+use crate::materials::materials::ScatterRecord;
+use crate::materials::microfacet::{self, Ggx};
+use crate::prelude::*;
+
+/// Represents a rough dielectric (frosted glass) using the microfacet model of Walter et al. (2007).
+///
+/// Both reflection and transmission are scattered by GGX microfacets and split using the exact
+/// Fresnel equations. Light travelling inside the material is absorbed following Beer-Lambert.
+#[derive(Debug, Clone)]
+pub(crate) struct RoughDielectric {
+    /// The index of refraction of the material.
+    refraction_index: f64,
+    /// The surface roughness in [0, 1], read from the first channel of the texture.
+    roughness: Arc<Textures>,
+    /// The absorption coefficient per unit distance travelled inside the material.
+    absorption: Color3,
+}
+
+/// The quantities shared by sampling and evaluation at a single hit.
+struct LocalFrame {
+    frame: Onb,
+    wo: Vec3,
+    eta: f64,
+    distribution: Ggx,
+}
+
+impl RoughDielectric {
+    /// Creates a new `RoughDielectric` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction of the material.
+    /// * `roughness` - The surface roughness in [0, 1], taken from the first channel of the texture.
+    /// * `absorption` - The Beer-Lambert absorption coefficient per unit distance, per color channel.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `RoughDielectric`.
+    pub(crate) fn new(refraction_index: f64, roughness: Arc<Textures>, absorption: Color3) -> Self {
+        Self {
+            refraction_index,
+            roughness,
+            absorption,
+        }
+    }
+
+    /// Builds the local shading frame for a hit, with the normal facing the incoming ray.
+    fn local_frame(&self, ray_in: &Ray, hit_record: &HitRecord) -> LocalFrame {
+        let frame = Onb::new(&hit_record.shading_normal);
+        let wo = frame.inverse_transform(&(-ray_in.direction.normalize()));
+        let eta = if hit_record.front_face {
+            self.refraction_index
+        } else {
+            1. / self.refraction_index
+        };
+        let roughness = self.roughness.sample(hit_record).x;
+
+        LocalFrame {
+            frame,
+            wo,
+            eta,
+            distribution: Ggx::new(roughness, roughness),
+        }
+    }
+
+    /// Computes the Beer-Lambert attenuation for a ray that travelled inside the material to this hit.
+    fn transmittance(&self, ray_in: &Ray, hit_record: &HitRecord) -> Color3 {
+        if hit_record.front_face {
+            return Color3::new(1., 1., 1.);
+        }
+
+        let distance = hit_record.t * ray_in.direction.length();
+        Color3::new(
+            (-self.absorption.x * distance).exp(),
+            (-self.absorption.y * distance).exp(),
+            (-self.absorption.z * distance).exp(),
+        )
+    }
+
+    /// Computes how a ray scatters when it hits the rough dielectric.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point, including the normal and whether the hit was on the front face.
+    /// * `rng` - A random number generator used for sampling microfacets and choosing between reflection and refraction.
+    ///
+    /// # Returns
+    ///
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
+    /// If `None` is returned, the ray is absorbed.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        let local = self.local_frame(ray_in, hit_record);
+        let wo = local.wo;
+        if wo.z <= 0. {
+            return None;
+        }
+        let transmittance = self.transmittance(ray_in, hit_record);
+
+        let smooth = local.distribution.is_smooth();
+        let wm = if smooth {
+            Vec3::new(0., 0., 1.)
+        } else {
+            local.distribution.sample_visible_normal(&wo, rng)
+        };
+
+        let reflectance = fresnel::dielectric(wo.dot(&wm), local.eta);
+        let (wi, pdf) = if gen_01(rng) < reflectance {
+            let wi = microfacet::reflect(&wo, &wm);
+            if wi.z <= 0. {
+                return None;
+            }
+            let pdf = local.distribution.visible_d(&wo, &wm) / (4. * wo.dot(&wm)) * reflectance;
+            (wi, pdf)
+        } else {
+            let wi = microfacet::refract(&wo, &wm, local.eta)?;
+            if wi.z >= 0. {
+                return None;
+            }
+            let denom = wi.dot(&wm) + wo.dot(&wm) / local.eta;
+            let dwm_dwi = wi.dot(&wm).abs() / (denom * denom);
+            let pdf = local.distribution.visible_d(&wo, &wm) * dwm_dwi * (1. - reflectance);
+            (wi, pdf)
+        };
+
+        // Choosing the lobe by Fresnel cancels it out of the weight, leaving only the
+        // masking-shadowing ratio of visible normal sampling.
+        let attenuation = if smooth {
+            transmittance
+        } else {
+            transmittance * (local.distribution.g2(&wo, &wi) / local.distribution.g1(&wo))
+        };
+
+        Some(ScatterRecord {
+            attenuation,
+            scattered: hit_record.spawn_ray(local.frame.transform(&wi), ray_in.time),
+            pdf: if smooth { None } else { Some(pdf) },
+        })
+    }
+
+    /// Returns the generalized half vector for a pair of directions, facing `+z`.
+    fn half_vector(wo: &Vec3, wi: &Vec3, eta: f64) -> Vec3 {
+        let wm = if wi.z > 0. { *wo + *wi } else { *wo + *wi * eta };
+        let wm = wm.normalize();
+        if wm.z < 0. {
+            -wm
+        } else {
+            wm
+        }
+    }
+
+    /// Evaluates the BSDF times the cosine term for light arriving from `direction`.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        let local = self.local_frame(ray_in, hit_record);
+        if local.distribution.is_smooth() || local.wo.z <= 0. {
+            return Color3::zero();
+        }
+
+        let wo = local.wo;
+        let wi = local.frame.inverse_transform(&direction.normalize());
+        if wi.z == 0. {
+            return Color3::zero();
+        }
+
+        let wm = Self::half_vector(&wo, &wi, local.eta);
+        // Discard microfacets that face away from either direction.
+        if wm.dot(&wi) * wi.z < 0. || wm.dot(&wo) * wo.z < 0. {
+            return Color3::zero();
+        }
+
+        let reflectance = fresnel::dielectric(wo.dot(&wm), local.eta);
+        let d = local.distribution.d(&wm);
+        let g = local.distribution.g2(&wo, &wi);
+
+        let value = if wi.z > 0. {
+            d * g * reflectance / (4. * wo.z)
+        } else {
+            let denom = wi.dot(&wm) + wo.dot(&wm) / local.eta;
+            (1. - reflectance) * d * g * (wi.dot(&wm) * wo.dot(&wm)).abs() / (wo.z * denom * denom)
+        };
+
+        self.transmittance(ray_in, hit_record) * value
+    }
+
+    /// Returns the pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        let local = self.local_frame(ray_in, hit_record);
+        if local.distribution.is_smooth() || local.wo.z <= 0. {
+            return 0.;
+        }
+
+        let wo = local.wo;
+        let wi = local.frame.inverse_transform(&direction.normalize());
+        if wi.z == 0. {
+            return 0.;
+        }
+
+        let wm = Self::half_vector(&wo, &wi, local.eta);
+        if wm.dot(&wi) * wi.z < 0. || wm.dot(&wo) * wo.z < 0. {
+            return 0.;
+        }
+
+        let reflectance = fresnel::dielectric(wo.dot(&wm), local.eta);
+        let visible = local.distribution.visible_d(&wo, &wm);
+        if wi.z > 0. {
+            visible / (4. * wo.dot(&wm).abs()) * reflectance
+        } else {
+            let denom = wi.dot(&wm) + wo.dot(&wm) / local.eta;
+            visible * wi.dot(&wm).abs() / (denom * denom) * (1. - reflectance)
+        }
+    }
+}
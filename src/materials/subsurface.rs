@@ -0,0 +1,143 @@
+use crate::materials::fresnel;
+use crate::materials::materials::ScatterRecord;
+use crate::materials::phase;
+use crate::prelude::*;
+
+/// A translucent material, like skin, marble, wax or milk, rendered with a volumetric random walk.
+///
+/// The surface is a smooth dielectric boundary. Light refracted into the object travels
+/// through a homogeneous scattering medium: every time a ray inside the object reaches the
+/// boundary again, a free-flight distance is sampled along the segment it just travelled,
+/// like `Medium::hit` does, and the ray either scattered somewhere along it or reaches the
+/// boundary. Each step of the walk is one bounce of the path, so dense, bright media need
+/// a larger `max_depth` on the camera.
+#[derive(Debug, Clone)]
+pub(crate) struct Subsurface {
+    /// The single-scattering albedo `σs / σt`, per color channel.
+    albedo: Color3,
+    /// The extinction coefficient `σt`, the inverse of the mean free path, per color channel.
+    extinction: Color3,
+    /// The Henyey-Greenstein asymmetry parameter of scattering events.
+    anisotropy: f64,
+    /// The index of refraction of the boundary.
+    refraction_index: f64,
+}
+
+impl Subsurface {
+    /// Creates a new `Subsurface` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - The single-scattering albedo in [0, 1], per color channel.
+    /// * `mean_free_path` - The average distance light travels between interactions, in scene units, per color channel.
+    /// * `anisotropy` - The Henyey-Greenstein asymmetry parameter in (-1, 1).
+    /// * `refraction_index` - The index of refraction of the boundary.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Subsurface`.
+    pub(crate) fn new(albedo: Color3, mean_free_path: Color3, anisotropy: f64, refraction_index: f64) -> Self {
+        let extinction = |d: f64| 1. / d.max(1e-6);
+        Self {
+            albedo,
+            extinction: Color3::new(
+                extinction(mean_free_path.x),
+                extinction(mean_free_path.y),
+                extinction(mean_free_path.z),
+            ),
+            anisotropy: anisotropy.clamp(-0.99, 0.99),
+            refraction_index,
+        }
+    }
+
+    /// Computes how a ray scatters when it hits the boundary, from either side.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The incoming ray hitting the material.
+    /// * `hit_record` - Information about the hit point, including whether the ray arrived from inside.
+    /// * `rng` - A random number generator used for sampling distances, directions and the boundary.
+    ///
+    /// # Returns
+    ///
+    /// An optional `ScatterRecord` containing the attenuation color and the scattered ray.
+    /// If `None` is returned, the ray is absorbed.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        if !hit_record.front_face {
+            let direction = ray_in.direction.normalize();
+            let distance = hit_record.t * ray_in.direction.length();
+
+            // Sample the distance with the extinction of one randomly chosen channel, and
+            // weight by the pdf averaged over all three so no channel gets unbounded weights.
+            let sigma_t = self.extinction;
+            let channel = gen_int(0, 3, rng) as usize;
+            let flight = -(1. - gen_01(rng)).ln() / sigma_t[channel];
+
+            if flight < distance {
+                let transmittance = Self::transmittance(&sigma_t, flight);
+                let pdf = mean(&(sigma_t * transmittance));
+                let attenuation = self.albedo * sigma_t * transmittance / pdf;
+
+                let point = ray_in.origin + flight * direction;
+                let scattered = phase::sample_henyey_greenstein(&direction, self.anisotropy, rng);
+                return Some(ScatterRecord {
+                    attenuation,
+                    scattered: Ray::new_time(point, scattered, ray_in.time),
+                    pdf: None,
+                });
+            }
+
+            let transmittance = Self::transmittance(&sigma_t, distance);
+            let srec = self.cross_boundary(ray_in, hit_record, rng);
+            return Some(ScatterRecord {
+                attenuation: transmittance / mean(&transmittance),
+                ..srec
+            });
+        }
+
+        Some(self.cross_boundary(ray_in, hit_record, rng))
+    }
+
+    /// Reflects or refracts a ray at the smooth boundary, choosing by the Fresnel reflectance.
+    fn cross_boundary(&self, ray_in: &Ray, hit_record: &HitRecord, rng: &mut ThreadRng) -> ScatterRecord {
+        let eta = if hit_record.front_face {
+            self.refraction_index
+        } else {
+            1. / self.refraction_index
+        };
+        let direction = ray_in.direction.normalize();
+        let cos_theta = (-direction.dot(&hit_record.shading_normal)).min(1.);
+
+        let reflectance = fresnel::dielectric(cos_theta, eta);
+        let scattered = if gen_01(rng) < reflectance {
+            direction.reflect(&hit_record.shading_normal)
+        } else {
+            direction.refract(&hit_record.shading_normal, 1. / eta)
+        };
+
+        ScatterRecord {
+            attenuation: Color3::new(1., 1., 1.),
+            scattered: hit_record.spawn_ray(scattered, ray_in.time),
+            pdf: None,
+        }
+    }
+
+    /// The fraction of light surviving a distance through the medium, per color channel.
+    fn transmittance(sigma_t: &Color3, distance: f64) -> Color3 {
+        Color3::new(
+            (-sigma_t.x * distance).exp(),
+            (-sigma_t.y * distance).exp(),
+            (-sigma_t.z * distance).exp(),
+        )
+    }
+}
+
+/// The average of the three color channels.
+fn mean(color: &Color3) -> f64 {
+    (color.x + color.y + color.z) / 3.
+}
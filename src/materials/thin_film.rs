@@ -0,0 +1,134 @@
+use crate::materials::fresnel;
+use crate::prelude::*;
+
+/// The wavelengths in nanometers the red, green and blue channels are evaluated at outside spectral mode.
+const RGB_WAVELENGTHS: [f64; 3] = [630., 532., 465.];
+
+/// A thin transparent coating, such as a soap film, oil slick or anodized oxide layer.
+///
+/// Light reflected by the top and the bottom of the film interferes, so the reflectance
+/// varies with wavelength, viewing angle and film thickness, producing iridescent colors.
+#[derive(Debug, Clone)]
+pub struct ThinFilm {
+    /// The index of refraction of the film.
+    ior: f64,
+    /// The thickness of the film in nanometers.
+    thickness: f64,
+    /// An optional texture whose first channel scales `thickness` over the surface.
+    thickness_map: Option<Arc<Textures>>,
+}
+
+impl ThinFilm {
+    /// Creates a film of uniform thickness.
+    ///
+    /// # Arguments
+    ///
+    /// * `ior` - The index of refraction of the film.
+    /// * `thickness` - The thickness of the film in nanometers; visible colors appear roughly between 100 and 1000.
+    ///
+    /// # Returns
+    ///
+    /// A new `ThinFilm`.
+    #[allow(dead_code)]
+    pub fn new(ior: f64, thickness: f64) -> Self {
+        Self {
+            ior,
+            thickness,
+            thickness_map: None,
+        }
+    }
+
+    /// Creates a film whose thickness varies over the surface, e.g. with a noise texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `ior` - The index of refraction of the film.
+    /// * `thickness` - The maximum thickness of the film in nanometers.
+    /// * `thickness_map` - A texture whose first channel, in [0, 1], scales the thickness.
+    ///
+    /// # Returns
+    ///
+    /// A new `ThinFilm`.
+    #[allow(dead_code)]
+    pub fn textured(ior: f64, thickness: f64, thickness_map: Arc<Textures>) -> Self {
+        Self {
+            ior,
+            thickness,
+            thickness_map: Some(thickness_map),
+        }
+    }
+
+    /// Returns the thickness of the film at a hit, in nanometers.
+    fn thickness(&self, hit_record: &HitRecord) -> f64 {
+        match &self.thickness_map {
+            Some(map) => {
+                let scale = map.sample(hit_record).x;
+                self.thickness * scale.max(0.)
+            }
+            None => self.thickness,
+        }
+    }
+
+    /// Computes the reflectance of a substrate covered by the film.
+    ///
+    /// # Arguments
+    ///
+    /// * `cos_theta` - The cosine of the angle of incidence, in the outside medium.
+    /// * `outside_ior` - The index of refraction of the medium the light arrives from.
+    /// * `eta` - The real part of the substrate's index of refraction, per color channel.
+    /// * `k` - The imaginary part of the substrate's index of refraction, per color channel.
+    /// * `hit_record` - The hit, used to look up the thickness.
+    /// * `wavelength` - The wavelength of the ray in spectral mode, or `None` to evaluate
+    ///   each color channel at a representative wavelength.
+    ///
+    /// # Returns
+    ///
+    /// The reflectance of each channel, in [0, 1].
+    pub(crate) fn reflectance(
+        &self,
+        cos_theta: f64,
+        outside_ior: f64,
+        eta: &Color3,
+        k: &Color3,
+        hit_record: &HitRecord,
+        wavelength: Option<f64>,
+    ) -> Color3 {
+        let thickness = self.thickness(hit_record);
+        let channel = |eta: f64, k: f64, wavelength: f64| {
+            fresnel::thin_film(cos_theta, outside_ior, self.ior, thickness, eta, k, wavelength)
+        };
+
+        match wavelength {
+            Some(wavelength) => {
+                let value = channel(
+                    at_wavelength(eta, wavelength),
+                    at_wavelength(k, wavelength),
+                    wavelength,
+                );
+                Color3::new(value, value, value)
+            }
+            None => Color3::new(
+                channel(eta.x, k.x, RGB_WAVELENGTHS[0]),
+                channel(eta.y, k.y, RGB_WAVELENGTHS[1]),
+                channel(eta.z, k.z, RGB_WAVELENGTHS[2]),
+            ),
+        }
+    }
+}
+
+/// Interpolates a per-channel optical constant to a wavelength, treating each channel
+/// as a sample at its representative wavelength.
+fn at_wavelength(value: &Color3, wavelength: f64) -> f64 {
+    let [red, green, blue] = RGB_WAVELENGTHS;
+    if wavelength >= red {
+        value.x
+    } else if wavelength >= green {
+        let t = (wavelength - green) / (red - green);
+        value.y + t * (value.x - value.y)
+    } else if wavelength >= blue {
+        let t = (wavelength - blue) / (green - blue);
+        value.z + t * (value.y - value.z)
+    } else {
+        value.z
+    }
+}
@@ -0,0 +1,102 @@
+use crate::materials::materials::ScatterRecord;
+use crate::materials::phase::PhaseFunction;
+use crate::prelude::*;
+
+/// The scattering behaviour of a participating medium with an arbitrary phase function.
+///
+/// The phase function is sampled exactly, so scattered rays are weighted by the albedo
+/// alone, and `eval` and `pdf` let direct light sampling work inside the medium.
+#[derive(Debug, Clone)]
+pub(crate) struct Volume {
+    /// The color of scattered light.
+    tex: Arc<Textures>,
+    /// The angular distribution of scattered light.
+    phase: PhaseFunction,
+    /// The fraction of interactions that scatter rather than absorb.
+    albedo: f64,
+    /// The radiance emitted at each interaction, already weighted by the fraction that absorb.
+    emission: Color3,
+}
+
+impl Volume {
+    /// Creates a new `Volume` material.
+    ///
+    /// # Arguments
+    ///
+    /// * `tex` - The albedo of the medium, looked up at the scattering point.
+    /// * `phase` - The phase function of the medium.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Volume`.
+    pub(crate) fn new(tex: Arc<Textures>, phase: PhaseFunction) -> Self {
+        Self::with_coefficients(tex, phase, 1., Color3::zero())
+    }
+
+    /// Creates a `Volume` material for a medium that also absorbs and emits light.
+    ///
+    /// # Arguments
+    ///
+    /// * `tex` - The color of scattered light.
+    /// * `phase` - The phase function of the medium.
+    /// * `albedo` - The fraction of interactions that scatter, `σs / σt`.
+    /// * `emission` - The emitted radiance times the fraction of interactions that absorb, `σa / σt`.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `Volume`.
+    pub(crate) fn with_coefficients(tex: Arc<Textures>, phase: PhaseFunction, albedo: f64, emission: Color3) -> Self {
+        Self {
+            tex,
+            phase,
+            albedo,
+            emission,
+        }
+    }
+
+    /// Returns the color of scattered light at a hit.
+    fn albedo(&self, hit_record: &HitRecord) -> Color3 {
+        self.tex.sample(hit_record) * self.albedo
+    }
+
+    /// Scatters a ray at a point inside the medium.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_in` - The ray travelling through the medium.
+    /// * `hit_record` - The scattering point.
+    /// * `rng` - A random number generator used for sampling the phase function.
+    ///
+    /// # Returns
+    ///
+    /// A `ScatterRecord` weighted by the albedo.
+    pub(crate) fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut ThreadRng,
+    ) -> Option<ScatterRecord> {
+        let incoming = ray_in.direction.normalize();
+        let direction = self.phase.sample(&incoming, rng);
+        Some(ScatterRecord {
+            attenuation: self.albedo(hit_record),
+            scattered: hit_record.spawn_ray(direction, ray_in.time),
+            pdf: Some(self.phase.eval(&incoming, &direction)),
+        })
+    }
+
+    /// Evaluates the albedo times the phase function for light arriving from `direction`.
+    pub(crate) fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color3 {
+        self.albedo(hit_record) * self.pdf(ray_in, direction)
+    }
+
+    /// Returns the pdf with which `scatter` picks `direction`.
+    pub(crate) fn pdf(&self, ray_in: &Ray, direction: &Vec3) -> f64 {
+        self.phase.eval(&ray_in.direction.normalize(), &direction.normalize())
+    }
+
+    /// Returns the light emitted by the medium at an interaction.
+    pub(crate) fn emitted(&self) -> Color3 {
+        self.emission
+    }
+}
@@ -0,0 +1,73 @@
+use crate::prelude::*;
+
+/// An orthonormal basis built around a given direction.
+///
+/// # Fields
+/// - `u`, `v`: Two unit vectors spanning the plane perpendicular to `w`.
+/// - `w`: The unit vector the basis was built around.
+#[derive(Debug, Copy, Clone)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// Builds an orthonormal basis whose `w` axis points along `n`.
+    ///
+    /// # Arguments
+    /// - `n`: The direction of the `w` axis. Does not need to be normalized.
+    ///
+    /// # Returns
+    /// A new `Onb` with arbitrary, but consistent, `u` and `v` axes.
+    pub fn new(n: &Vec3) -> Self {
+        let w = n.normalize();
+        let a = if w.x.abs() > 0.9 {
+            Vec3::new(0., 1., 0.)
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let v = w.cross(&a).normalize();
+        let u = w.cross(&v);
+
+        Self { u, v, w }
+    }
+
+    /// Builds an orthonormal basis whose `w` axis points along `n` and whose `u` axis follows
+    /// `tangent`, projected onto the plane perpendicular to `n`.
+    ///
+    /// # Arguments
+    /// - `n`: The direction of the `w` axis. Does not need to be normalized.
+    /// - `tangent`: The direction for the `u` axis, like the `dpdu` of a surface.
+    ///
+    /// # Returns
+    /// A new `Onb` tied to the tangent, or the arbitrary basis of `Onb::new` when the tangent is
+    /// zero or parallel to `n`.
+    pub fn from_tangent(n: &Vec3, tangent: &Vec3) -> Self {
+        let w = n.normalize();
+        let u = *tangent - tangent.dot(&w) * w;
+        if u.length() <= 1e-8 * tangent.length() {
+            return Self::new(n);
+        }
+        let u = u.normalize();
+        let v = w.cross(&u);
+
+        Self { u, v, w }
+    }
+
+    /// Transforms a vector from basis coordinates to world coordinates.
+    pub fn transform(&self, v: &Vec3) -> Vec3 {
+        v.x * self.u + v.y * self.v + v.z * self.w
+    }
+
+    /// Transforms a non-negative bound, like a rounding error, from basis coordinates into
+    /// world space, so that it bounds the transformed vector whatever the signs.
+    pub fn transform_abs(&self, v: &Vec3) -> Vec3 {
+        v.x * self.u.abs() + v.y * self.v.abs() + v.z * self.w.abs()
+    }
+
+    /// Transforms a world-space vector into basis coordinates.
+    pub fn inverse_transform(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(v.dot(&self.u), v.dot(&self.v), v.dot(&self.w))
+    }
+}
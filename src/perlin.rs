@@ -10,6 +10,7 @@ pub struct Perlin {
 }
 
 impl Perlin {
+    #[allow(clippy::needless_range_loop)]
     pub fn new(rng: &mut ThreadRng) -> Self {
 
         let mut randvec = [Vec3::zero(); POINT_COUNT];
@@ -28,6 +29,7 @@ impl Perlin {
         }
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn noise(&self, p: &Point3) -> f64 {
         let u = p.x - p.x.floor();
         let v = p.y - p.y.floor();
@@ -67,6 +69,7 @@ impl Perlin {
         accum.abs()
     }
 
+    #[allow(clippy::needless_range_loop)]
     fn trilinear_interp(c: [[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
         let uu = u*u*(3.-2.*u);
         let vv = v*v*(3.-2.*v);
@@ -91,6 +94,7 @@ impl Perlin {
         accum
     }
 
+    #[allow(clippy::needless_range_loop)]
     fn perlin_generate_perm(rng: &mut ThreadRng) -> [usize; POINT_COUNT] {
         let mut p = [0; POINT_COUNT];
         for i in 0..POINT_COUNT {
@@ -7,12 +7,14 @@ pub use rand::Rng;
 pub use std::sync::Arc;
 
 //internal
+pub use crate::backgrounds::backgrounds::Background;
 pub use crate::constants::*;
 pub use crate::hittables::hittables::Hittables;
 pub use crate::hittables::hit_record::HitRecord;
 pub use crate::hittables::aabb::AABB;
 pub use crate::interval::Interval;
 pub use crate::materials::materials::Materials;
+pub use crate::onb::Onb;
 pub use crate::random::*;
 pub use crate::ray::Ray;
 pub use crate::vec3::{Color3, Point3, Vec3};
@@ -1,5 +1,15 @@
 use crate::prelude::*;
 
+/// The rays through the neighbouring pixels, offset by one pixel horizontally and vertically,
+/// which tell how large the footprint of a ray is where it hits a surface.
+#[derive(Debug, Copy, Clone)]
+pub struct RayDifferentials {
+    pub rx_origin: Point3,
+    pub rx_direction: Vec3,
+    pub ry_origin: Point3,
+    pub ry_direction: Vec3,
+}
+
 /// Represents a ray in 3D space, defined by an origin point, a direction vector, and an optional time parameter.
 #[derive(Debug, Copy, Clone)]
 pub struct Ray {
@@ -9,6 +19,10 @@ pub struct Ray {
     pub direction: Vec3,
     /// The time parameter associated with the ray (useful for motion blur or time-dependent calculations).
     pub time: f64,
+    /// The wavelength in nanometers this ray carries in spectral mode, or `None` for RGB rendering.
+    pub wavelength: Option<f64>,
+    /// The rays through the neighbouring pixels, for camera rays, or `None` for scattered rays.
+    pub differentials: Option<RayDifferentials>,
 }
 
 impl Ray {
@@ -27,6 +41,8 @@ impl Ray {
             origin,
             direction,
             time: 0.0,
+            wavelength: None,
+            differentials: None,
         }
     }
 
@@ -46,6 +62,8 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelength: None,
+            differentials: None,
         }
     }
 
@@ -61,4 +79,18 @@ impl Ray {
     pub fn at(&self, t: f64) -> Point3 {
         self.origin + t * self.direction
     }
+
+    /// Scales the spacing of the differential rays, e.g. down when many samples share a pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The factor the offsets of the differential rays from this ray are scaled by.
+    pub fn scale_differentials(&mut self, scale: f64) {
+        if let Some(d) = &mut self.differentials {
+            d.rx_origin = self.origin + scale * (d.rx_origin - self.origin);
+            d.ry_origin = self.origin + scale * (d.ry_origin - self.origin);
+            d.rx_direction = self.direction + scale * (d.rx_direction - self.direction);
+            d.ry_direction = self.direction + scale * (d.ry_direction - self.direction);
+        }
+    }
 }
@@ -0,0 +1,178 @@
+use crate::prelude::*;
+use std::sync::OnceLock;
+
+/// The shortest wavelength traced in spectral mode, in nanometers.
+pub const LAMBDA_MIN: f64 = 380.;
+/// The longest wavelength traced in spectral mode, in nanometers.
+///
+/// This is where Smits' basis spectra end, so every traced wavelength has its own reflectance;
+/// the matching functions carry under 0.01% of the luminance of white beyond it.
+pub const LAMBDA_MAX: f64 = 720.;
+
+/// Smits (1999) basis spectra, tabulated in 10 equal bins from 380nm to 720nm.
+const SMITS_WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const SMITS_CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const SMITS_MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const SMITS_YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const SMITS_RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const SMITS_BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+/// CIE XYZ to linear sRGB (D65) matrix.
+const XYZ_TO_SRGB: [[f64; 3]; 3] = [
+    [3.2404542, -1.5371385, -0.4985314],
+    [-0.9692660, 1.8760108, 0.0415560],
+    [0.0556434, -0.2040259, 1.0572252],
+];
+
+/// Samples a wavelength from one stratum of the visible range.
+///
+/// Spreading the samples of a pixel evenly over the spectrum removes most of the color
+/// noise a purely random choice of wavelength would produce.
+///
+/// # Arguments
+///
+/// * `index` - The index of the sample within the pixel.
+/// * `count` - The number of samples taken for the pixel.
+/// * `rng` - A random number generator.
+///
+/// # Returns
+///
+/// A wavelength in nanometers; over all strata its pdf is `1 / (LAMBDA_MAX - LAMBDA_MIN)`.
+pub fn sample_wavelength(index: usize, count: usize, rng: &mut ThreadRng) -> f64 {
+    let u = (index as f64 + gen_01(rng)) / count as f64;
+    LAMBDA_MIN + u * (LAMBDA_MAX - LAMBDA_MIN)
+}
+
+/// Evaluates the spectrum Smits' method reconstructs from an RGB color at a single wavelength.
+///
+/// The color is split into a white part plus at most one secondary and one primary basis
+/// spectrum, which keeps reflectances in [0, 1] bounded while reproducing the RGB value.
+///
+/// # Arguments
+///
+/// * `color` - The linear RGB color, either a reflectance or a radiance.
+/// * `wavelength` - The wavelength in nanometers.
+///
+/// # Returns
+///
+/// The value of the upsampled spectrum at `wavelength`.
+pub fn rgb_to_spectrum(color: &Color3, wavelength: f64) -> f64 {
+    let t = (wavelength - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN) * 10.;
+    let bin = (t.max(0.) as usize).min(9);
+    let (r, g, b) = (color.x, color.y, color.z);
+
+    let value = if r <= g && r <= b {
+        let rest = if g <= b {
+            (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        };
+        r * SMITS_WHITE[bin] + rest
+    } else if g <= r && g <= b {
+        let rest = if r <= b {
+            (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        };
+        g * SMITS_WHITE[bin] + rest
+    } else {
+        let rest = if r <= g {
+            (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        };
+        b * SMITS_WHITE[bin] + rest
+    };
+    value.max(0.)
+}
+
+/// A piecewise Gaussian with different widths on either side of its peak.
+fn piecewise_gaussian(x: f64, mu: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let t = (x - mu) / if x < mu { sigma_low } else { sigma_high };
+    (-0.5 * t * t).exp()
+}
+
+/// Evaluates the CIE 1931 color matching functions using the multi-lobe fit of Wyman et al. (2013).
+///
+/// # Arguments
+///
+/// * `wavelength` - The wavelength in nanometers.
+///
+/// # Returns
+///
+/// The `x̄`, `ȳ` and `z̄` values, stored in a `Vec3`.
+fn cie_xyz(wavelength: f64) -> Vec3 {
+    let l = wavelength;
+    let x = 1.056 * piecewise_gaussian(l, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(l, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(l, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(l, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(l, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(l, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(l, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+/// Converts CIE XYZ to linear sRGB.
+fn xyz_to_rgb(xyz: &Vec3) -> Color3 {
+    let row = |m: [f64; 3]| m[0] * xyz.x + m[1] * xyz.y + m[2] * xyz.z;
+    Color3::new(row(XYZ_TO_SRGB[0]), row(XYZ_TO_SRGB[1]), row(XYZ_TO_SRGB[2]))
+}
+
+/// The RGB response of the matching functions to a constant unit spectrum over the traced range.
+///
+/// Dividing by it white balances the output, so an upsampled white still renders as white.
+fn white_point() -> Color3 {
+    static WHITE: OnceLock<Color3> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let steps = 4000;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let mut xyz = Vec3::zero();
+        for i in 0..steps {
+            let l = LAMBDA_MIN + (i as f64 + 0.5) * step;
+            xyz = xyz + cie_xyz(l) * step;
+        }
+        xyz_to_rgb(&xyz)
+    })
+}
+
+/// Converts a radiance sample carried at a single wavelength back to RGB.
+///
+/// The result is an unbiased estimate of the RGB color of the full spectrum when
+/// `wavelength` was drawn with `sample_wavelength`.
+///
+/// # Arguments
+///
+/// * `radiance` - The spectral radiance at `wavelength`.
+/// * `wavelength` - The wavelength in nanometers.
+///
+/// # Returns
+///
+/// The white-balanced linear RGB contribution of the sample.
+pub fn spectrum_to_rgb(radiance: f64, wavelength: f64) -> Color3 {
+    let white = white_point();
+    let rgb = xyz_to_rgb(&cie_xyz(wavelength)) * (radiance * (LAMBDA_MAX - LAMBDA_MIN));
+    Color3::new(rgb.x / white.x, rgb.y / white.y, rgb.z / white.z)
+}
+
+/// Projects an RGB color onto the wavelength a ray carries.
+///
+/// # Arguments
+///
+/// * `color` - The linear RGB color.
+/// * `wavelength` - The wavelength of the ray, or `None` when rendering in RGB.
+///
+/// # Returns
+///
+/// `color` unchanged in RGB mode, otherwise its upsampled spectrum at `wavelength`
+/// replicated in all three channels.
+pub fn at_wavelength(color: Color3, wavelength: Option<f64>) -> Color3 {
+    match wavelength {
+        Some(wavelength) => {
+            let value = rgb_to_spectrum(&color, wavelength);
+            Color3::new(value, value, value)
+        }
+        None => color,
+    }
+}
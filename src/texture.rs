@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use std::io::ErrorKind;
 use image::ImageReader;
 use image::ImageBuffer;
 use image::Rgb;
@@ -29,20 +30,53 @@ impl Textures {
         Arc::new(Self::Noise(NoiseTexture::new(scale, rng)))
     }
 
-    pub fn image(path : &str) -> Arc<Self> {
-        let img= ImageTexture::new(path);
+    /// Loads an image texture, filtered trilinearly and clamped at its edges.
+    ///
+    /// # Returns
+    /// The texture, or an error naming the path if the image could not be opened or decoded.
+    pub fn image(path : &str) -> Result<Arc<Self>, Error> {
+        Self::image_with(path, TextureFilter::Trilinear, WrapMode::Clamp)
+    }
 
-        match img {
-            Ok(x) => Arc::new(Self::Img(x)),
-            _ => Self::rgb(0., 1., 1.),
-        }
+    /// Loads an image texture with a chosen filter and wrap mode.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the image file.
+    /// - `filter`: How texels are filtered over the footprint of a lookup.
+    /// - `wrap`: How texture coordinates outside [0, 1] are mapped back onto the image.
+    ///
+    /// # Returns
+    /// The texture, or an error naming the path if the image could not be opened or decoded.
+    pub fn image_with(path : &str, filter: TextureFilter, wrap: WrapMode) -> Result<Arc<Self>, Error> {
+        Ok(Arc::new(Self::Img(ImageTexture::new(path, filter, wrap)?)))
+    }
+
+    /// Loads an image texture like `image`, but warns and falls back to a magenta and black
+    /// checkerboard if it cannot be loaded, so a missing file shows up in the render instead
+    /// of stopping it.
+    pub fn image_or_placeholder(path : &str) -> Arc<Self> {
+        Self::image(path).unwrap_or_else(|error| {
+            eprintln!("Warning: {error}; using a placeholder texture");
+            Arc::new(Self::Img(ImageTexture::placeholder()))
+        })
     }
 
+    /// Looks the texture up at a single point, without a footprint to filter over.
     pub fn value(&self, u : f64, v : f64, p : &Point3) -> Color3 {
+        self.filtered(u, v, p, (0., 0.), (0., 0.))
+    }
+
+    /// Looks the texture up at a hit, filtered over the footprint its ray differentials cover.
+    pub fn sample(&self, hit_record: &HitRecord) -> Color3 {
+        self.filtered(hit_record.u, hit_record.v, &hit_record.point, hit_record.duvdx, hit_record.duvdy)
+    }
+
+    /// Looks the texture up over the footprint spanned by `duvdx` and `duvdy` around `(u, v)`.
+    fn filtered(&self, u : f64, v : f64, p : &Point3, duvdx: (f64, f64), duvdy: (f64, f64)) -> Color3 {
         match self {
-            Self::Checkered(c) => c.value(u, v, p),
+            Self::Checkered(c) => c.value(u, v, p, duvdx, duvdy),
             Self::Solid(s) => s.value(),
-            Self::Img(i) => i.value(u, v),
+            Self::Img(i) => i.value(u, v, duvdx, duvdy),
             Self::Noise(n) => n.value(p)
         }
     }
@@ -65,33 +99,103 @@ pub struct CheckerTexture {
 }
 
 impl CheckerTexture {
-    pub fn value(&self, u : f64, v : f64, p : &Point3) -> Color3 { 
+    pub fn value(&self, u : f64, v : f64, p : &Point3, duvdx: (f64, f64), duvdy: (f64, f64)) -> Color3 { 
         let x  = (self.inv_scale * p.x).floor() as i32;
         let y  = (self.inv_scale * p.y).floor() as i32;
         let z  = (self.inv_scale * p.z).floor() as i32;
 
         if (x + y + z) % 2 == 0 {
-            self.even.value(u, v, p)
+            self.even.filtered(u, v, p, duvdx, duvdy)
         } else {
-            self.odd.value(u, v, p)
+            self.odd.filtered(u, v, p, duvdx, duvdy)
         }
     }
 }
 
-#[derive(Debug, Clone)]    
+/// How an image texture filters its texels over the footprint of a lookup.
+///
+/// # Variants
+/// - `Nearest`: The single texel under the lookup, which aliases at a distance and looks blocky up close.
+/// - `Bilinear`: A blend of the four nearest texels of the full-resolution image.
+/// - `Trilinear`: Bilinear lookups in the two mip levels whose texels best match the size of
+///   the footprint, blended together.
+/// - `Ewa`: A Gaussian-weighted average over the elliptical footprint, in the mip level
+///   matching its minor axis, which stays sharp where surfaces are seen at grazing angles.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum TextureFilter {
+    Nearest,
+    Bilinear,
+    Trilinear,
+    Ewa,
+}
+
+/// How an image texture maps texture coordinates outside [0, 1] back onto the image.
+///
+/// # Variants
+/// - `Clamp`: Repeats the texels along the edges.
+/// - `Repeat`: Tiles the image.
+/// - `Mirror`: Tiles the image, flipping every other tile so the tiles meet seamlessly.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum WrapMode {
+    Clamp,
+    Repeat,
+    Mirror,
+}
+
+impl WrapMode {
+    /// Maps a texel index onto an image `size` texels wide.
+    fn texel(&self, i: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let i = match self {
+            Self::Clamp => i.clamp(0, size - 1),
+            Self::Repeat => i.rem_euclid(size),
+            Self::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size { i } else { 2 * size - 1 - i }
+            }
+        };
+        i as u32
+    }
+}
+
+/// The longest the major axis of an EWA footprint may be relative to its minor axis; longer
+/// footprints are widened, trading some blur for a bounded number of texels.
+const MAX_ANISOTROPY: f64 = 8.;
+
+/// How sharply the Gaussian of EWA filtering falls off towards the edge of the footprint.
+const EWA_ALPHA: f64 = 2.;
+
+type Image = ImageBuffer<Rgb<f32>, Vec<f32>>;
+
+#[derive(Debug, Clone)]
 pub struct ImageTexture {
-    image : ImageBuffer<Rgb<f32>, Vec<f32>>
+    /// The mip-map pyramid, from the full-resolution image down to a single texel, each
+    /// level half the size of the one before.
+    levels : Vec<Image>,
+    filter : TextureFilter,
+    wrap : WrapMode,
 }
 
 impl ImageTexture {
-    pub fn new(path : &str) -> Result<Self, Error> {
+    /// Loads an image texture, converting its sRGB texels to linear values.
+    ///
+    /// # Returns
+    /// The texture, or an error of the form `"<path>: <reason>"` if the image could not be
+    /// opened, could not be decoded or is empty.
+    pub fn new(path : &str, filter: TextureFilter, wrap: WrapMode) -> Result<Self, Error> {
+        let failed = |kind: ErrorKind, reason: &dyn std::fmt::Display| Error::new(kind, format!("{path}: {reason}"));
         let image = ImageReader::open(path)
-        .expect("Failed to open image")
+        .map_err(|e| failed(e.kind(), &e))?
         .decode()
-        .expect("Failed to decode image")
+        .map_err(|e| failed(ErrorKind::InvalidData, &e))?
         .to_rgb8();
 
         let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return Err(failed(ErrorKind::InvalidData, &"the image has no pixels"));
+        }
         let mut buf = ImageBuffer::new(width, height);
 
         for (x, y, pixel) in image.enumerate_pixels() {
@@ -103,10 +207,18 @@ impl ImageTexture {
             buf.put_pixel(x, y, linear);
         }
 
-        Ok(Self {image : buf})
+        Ok(Self {levels : Self::pyramid(buf), filter, wrap})
 
     }
 
+    /// A magenta and black checkerboard of 8 x 8 squares, the usual sign of a missing texture.
+    fn placeholder() -> Self {
+        let image = ImageBuffer::from_fn(8, 8, |x, y| {
+            if (x + y) % 2 == 0 { Rgb([1., 0., 1.]) } else { Rgb([0., 0., 0.]) }
+        });
+        Self {levels : Self::pyramid(image), filter : TextureFilter::Nearest, wrap : WrapMode::Repeat}
+    }
+
     fn srgb_to_linear_f32(c: u8) -> f32 {
         let c = c as f32 / 255.0;
         if c <= 0.04045 {
@@ -116,35 +228,178 @@ impl ImageTexture {
         }
     }
 
-    pub fn value(&self, u : f64, v : f64) -> Color3 { 
-        let image = &self.image;
-        // If we have no texture data, then return solid cyan as a debugging aid.
-        if image.height() <= 0 {return Color3::new(0.,1.,1.)};
+    /// Builds the mip-map pyramid of an image by repeatedly averaging blocks of 2x2 texels.
+    fn pyramid(image: Image) -> Vec<Image> {
+        let mut levels = vec![image];
+        loop {
+            let last = &levels[levels.len() - 1];
+            let (width, height) = last.dimensions();
+            if width <= 1 && height <= 1 {
+                return levels;
+            }
+
+            let next = ImageBuffer::from_fn(width.div_ceil(2), height.div_ceil(2), |x, y| {
+                let mut sum = [0f32; 3];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let texel = last.get_pixel((2 * x + dx).min(width - 1), (2 * y + dy).min(height - 1));
+                    for c in 0..3 {
+                        sum[c] += texel[c] / 4.;
+                    }
+                }
+                Rgb(sum)
+            });
+            levels.push(next);
+        }
+    }
+
+    /// Looks the texture up over the footprint spanned by `duvdx` and `duvdy` around `(u, v)`.
+    ///
+    /// Lookups without a footprint, like those of scattered rays, which carry no differentials,
+    /// read the full-resolution image.
+    pub fn value(&self, u : f64, v : f64, duvdx: (f64, f64), duvdy: (f64, f64)) -> Color3 {
+        // Flip V to image coordinates
+        let (s, t) = (u, 1. - v);
+        let (dstdx, dstdy) = ((duvdx.0, -duvdx.1), (duvdy.0, -duvdy.1));
 
-        // Clamp input texture coordinates to [0,1] x [1,0]
-        let bounds = Interval::new(0., 1.);
-        let u = bounds.clamp(u);
-        let v = 1. - bounds.clamp(v);  // Flip V to image coordinates
+        match self.filter {
+            TextureFilter::Nearest => {
+                let image = &self.levels[0];
+                let x = (s * image.width() as f64).floor() as i64;
+                let y = (t * image.height() as f64).floor() as i64;
+                self.texel(0, x, y)
+            }
+            TextureFilter::Bilinear => self.bilinear(0, s, t),
+            TextureFilter::Trilinear => {
+                let width = 2. * [dstdx.0, dstdx.1, dstdy.0, dstdy.1].iter().fold(0., |m: f64, d| m.max(d.abs()));
+                self.between_levels(self.level_of_detail(width), |level| self.bilinear(level, s, t))
+            }
+            TextureFilter::Ewa => self.ewa(s, t, dstdx, dstdy),
+        }
+    }
 
-        let i = ((u * image.width() as f64).min(image.width() as f64 - 1.0)) as u32;
-        let j = ((v * image.height() as f64).min(image.height() as f64 - 1.0)) as u32;
-        let pixel = image.get_pixel(i,j);
+    /// Reads a texel of a mip level, wrapping its indices onto the level.
+    fn texel(&self, level: usize, x: i64, y: i64) -> Color3 {
+        let image = &self.levels[level];
+        let pixel = image.get_pixel(self.wrap.texel(x, image.width()), self.wrap.texel(y, image.height()));
 
-        Color3::new(pixel[0].into(), 
-                    pixel[1].into(), 
+        Color3::new(pixel[0].into(),
+                    pixel[1].into(),
                     pixel[2].into())
     }
+
+    /// Blends the four texels of a mip level nearest to the image coordinates `(s, t)`.
+    fn bilinear(&self, level: usize, s: f64, t: f64) -> Color3 {
+        let image = &self.levels[level];
+        let x = s * image.width() as f64 - 0.5;
+        let y = t * image.height() as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        (1. - fx) * (1. - fy) * self.texel(level, x0, y0)
+            + fx * (1. - fy) * self.texel(level, x0 + 1, y0)
+            + (1. - fx) * fy * self.texel(level, x0, y0 + 1)
+            + fx * fy * self.texel(level, x0 + 1, y0 + 1)
+    }
+
+    /// The fractional mip level whose texels are as wide as `width`, in image coordinates.
+    fn level_of_detail(&self, width: f64) -> f64 {
+        let (w, h) = self.levels[0].dimensions();
+        let texels = width * w.max(h) as f64;
+        texels.max(1e-8).log2().clamp(0., (self.levels.len() - 1) as f64)
+    }
+
+    /// Blends a lookup in the two mip levels around the fractional `level`.
+    fn between_levels(&self, level: f64, lookup: impl Fn(usize) -> Color3) -> Color3 {
+        let lower = level.floor() as usize;
+        if lower + 1 >= self.levels.len() {
+            return lookup(self.levels.len() - 1);
+        }
+        let blend = level - lower as f64;
+        if blend == 0. {
+            return lookup(lower);
+        }
+        (1. - blend) * lookup(lower) + blend * lookup(lower + 1)
+    }
+
+    /// Filters over the ellipse whose axes are the footprint differentials, as in elliptically
+    /// weighted averaging.
+    fn ewa(&self, s: f64, t: f64, mut major: (f64, f64), mut minor: (f64, f64)) -> Color3 {
+        let length = |d: (f64, f64)| (d.0 * d.0 + d.1 * d.1).sqrt();
+        if length(major) < length(minor) {
+            std::mem::swap(&mut major, &mut minor);
+        }
+        let major_length = length(major);
+        let mut minor_length = length(minor);
+        if minor_length == 0. {
+            return self.bilinear(0, s, t);
+        }
+
+        // Widen very eccentric footprints, which would cover too many texels.
+        if minor_length * MAX_ANISOTROPY < major_length {
+            let scale = major_length / (minor_length * MAX_ANISOTROPY);
+            minor = (minor.0 * scale, minor.1 * scale);
+            minor_length *= scale;
+        }
+
+        // Footprints wider than the image only see its average, the last level.
+        let level = self.level_of_detail(minor_length);
+        let last = self.levels.len() - 1;
+        if level >= last as f64 {
+            return self.texel(last, 0, 0);
+        }
+        self.between_levels(level, |level| self.ewa_level(level, s, t, major, minor))
+    }
+
+    /// Averages the texels of a mip level inside the ellipse with axes `major` and `minor` around
+    /// `(s, t)`, weighted by a Gaussian of their distance from the center.
+    fn ewa_level(&self, level: usize, s: f64, t: f64, major: (f64, f64), minor: (f64, f64)) -> Color3 {
+        let image = &self.levels[level];
+        let (w, h) = (image.width() as f64, image.height() as f64);
+        let (x0, y0) = (s * w - 0.5, t * h - 0.5);
+        let (major, minor) = ((major.0 * w, major.1 * h), (minor.0 * w, minor.1 * h));
+
+        // The implicit ellipse `a x^2 + b x y + c y^2 < 1`, grown by a texel so that it
+        // always covers at least one.
+        let mut a = major.1 * major.1 + minor.1 * minor.1 + 1.;
+        let mut b = -2. * (major.0 * major.1 + minor.0 * minor.1);
+        let mut c = major.0 * major.0 + minor.0 * minor.0 + 1.;
+        let inv_f = 1. / (a * c - b * b * 0.25);
+        a *= inv_f;
+        b *= inv_f;
+        c *= inv_f;
+
+        let det = 4. * a * c - b * b;
+        let half_width = 2. * (det * c).sqrt() / det;
+        let half_height = 2. * (det * a).sqrt() / det;
+
+        let mut sum = Color3::zero();
+        let mut total = 0.;
+        for y in (y0 - half_height).ceil() as i64..=(y0 + half_height).floor() as i64 {
+            let dy = y as f64 - y0;
+            for x in (x0 - half_width).ceil() as i64..=(x0 + half_width).floor() as i64 {
+                let dx = x as f64 - x0;
+                let r2 = a * dx * dx + b * dx * dy + c * dy * dy;
+                if r2 < 1. {
+                    let weight = (-EWA_ALPHA * r2).exp() - (-EWA_ALPHA).exp();
+                    sum = sum + weight * self.texel(level, x, y);
+                    total += weight;
+                }
+            }
+        }
+        if total > 0. { sum / total } else { self.bilinear(level, s, t) }
+    }
 }
 #[derive(Debug, Clone)]
 pub struct NoiseTexture {
-    noise: Perlin,
+    noise: Box<Perlin>,
     scale: f64,
 }
 
 impl NoiseTexture {
     pub fn new(scale: f64, rng: &mut ThreadRng) -> Self {
         Self {
-            noise: Perlin::new(rng),
+            noise: Box::new(Perlin::new(rng)),
             scale,
         }
     }
@@ -154,3 +409,34 @@ impl NoiseTexture {
         Color3::new(0.5, 0.5, 0.5) * (1. + turb.sin())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texels(wrap: WrapMode, size: u32) -> Vec<u32> {
+        (-5..9).map(|i| wrap.texel(i, size)).collect()
+    }
+
+    #[test]
+    fn clamp_repeats_the_edge_texels() {
+        assert_eq!(texels(WrapMode::Clamp, 4), [0, 0, 0, 0, 0, 0, 1, 2, 3, 3, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn repeat_tiles_the_image() {
+        assert_eq!(texels(WrapMode::Repeat, 4), [3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn mirror_reflects_at_every_edge() {
+        assert_eq!(texels(WrapMode::Mirror, 4), [3, 3, 2, 1, 0, 0, 1, 2, 3, 3, 2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn single_texel_images_always_map_to_it() {
+        for wrap in [WrapMode::Clamp, WrapMode::Repeat, WrapMode::Mirror] {
+            assert!(texels(wrap, 1).iter().all(|&t| t == 0));
+        }
+    }
+}
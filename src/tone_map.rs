@@ -0,0 +1,186 @@
+use crate::prelude::*;
+
+/// The operator used to compress scene-referred radiance into the displayable [0, 1] range.
+///
+/// Every operator works on linear values; the sRGB transfer function is applied afterwards
+/// by `Vec3::write_color`.
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+pub enum ToneMap {
+    /// Hard clamp to [0, 1], i.e. no tone mapping at all.
+    Clamp,
+    /// Reinhard's global operator `L / (1 + L)`, applied to luminance to preserve hue.
+    Reinhard,
+    /// Reinhard's extended operator, where a luminance of `white` maps exactly to 1.0.
+    ///
+    /// `white` is raised to at least `MIN_WHITE`, so zero or negative values saturate the
+    /// image instead of dividing by zero.
+    ExtendedReinhard { white: f64 },
+    /// Stephen Hill's fit of the ACES reference rendering and output transforms.
+    Aces,
+    /// Troy Sobotka's AgX base transform, using the polynomial fit of its default contrast curve.
+    AgX,
+}
+
+/// The smallest white point of `ToneMap::ExtendedReinhard`.
+const MIN_WHITE: f64 = 1e-3;
+
+/// sRGB to ACES AP1 (RRT_SAT) input matrix.
+const ACES_INPUT: [[f64; 3]; 3] = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+];
+
+/// ODT_SAT to sRGB output matrix.
+const ACES_OUTPUT: [[f64; 3]; 3] = [
+    [1.60475, -0.53108, -0.07367],
+    [-0.10208, 1.10813, -0.00605],
+    [-0.00327, -0.07276, 1.07602],
+];
+
+/// sRGB to AgX inset matrix.
+const AGX_INSET: [[f64; 3]; 3] = [
+    [0.842479062253094, 0.0784335999999992, 0.0792237451477643],
+    [0.0423282422610123, 0.878468636469772, 0.0791661274605434],
+    [0.0423756549057051, 0.0784336, 0.879142973793104],
+];
+
+/// AgX outset matrix, the inverse of `AGX_INSET`.
+const AGX_OUTSET: [[f64; 3]; 3] = [
+    [1.19687900512017, -0.0980208811401368, -0.0990297440797205],
+    [-0.0528968517574562, 1.15190312990417, -0.0989611768448433],
+    [-0.0529716355144438, -0.0980434501171241, 1.15107367264116],
+];
+
+/// The exposure range, in stops around middle grey, that AgX maps into [0, 1].
+const AGX_MIN_EV: f64 = -12.47393;
+const AGX_MAX_EV: f64 = 4.026069;
+
+impl ToneMap {
+    /// Maps a linear, exposure-adjusted color into the linear [0, 1] display range.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The scene-referred linear color.
+    ///
+    /// # Returns
+    ///
+    /// The display-referred linear color, with every component in [0, 1].
+    pub fn apply(&self, color: Color3) -> Color3 {
+        let mapped = match self {
+            Self::Clamp => color,
+            Self::Reinhard => Self::scale_luminance(color, |l| l / (1. + l)),
+            Self::ExtendedReinhard { white } => {
+                let white = white.max(MIN_WHITE);
+                let white_sq = white * white;
+                Self::scale_luminance(color, |l| l * (1. + l / white_sq) / (1. + l))
+            }
+            Self::Aces => Self::aces(color),
+            Self::AgX => Self::agx(color),
+        };
+
+        let unit = Interval::new(0., 1.);
+        Color3::new(unit.clamp(mapped.x), unit.clamp(mapped.y), unit.clamp(mapped.z))
+    }
+
+    /// Rescales a color so that its luminance follows `curve`, keeping its chromaticity.
+    fn scale_luminance(color: Color3, curve: impl Fn(f64) -> f64) -> Color3 {
+        let luminance = color.luminance();
+        if luminance <= 0. {
+            return Color3::zero();
+        }
+        color * (curve(luminance) / luminance)
+    }
+
+    fn aces(color: Color3) -> Color3 {
+        let v = Self::mat_mul(&ACES_INPUT, color);
+
+        let fit = |x: f64| {
+            let a = x * (x + 0.0245786) - 0.000090537;
+            let b = x * (0.983729 * x + 0.4329510) + 0.238081;
+            a / b
+        };
+
+        Self::mat_mul(&ACES_OUTPUT, Color3::new(fit(v.x), fit(v.y), fit(v.z)))
+    }
+
+    fn agx(color: Color3) -> Color3 {
+        let v = Self::mat_mul(&AGX_INSET, color);
+
+        let encode = |x: f64| {
+            let ev = x.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV);
+            let x = (ev - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+
+            let x2 = x * x;
+            let x4 = x2 * x2;
+            15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2
+                + 0.1191 * x
+                - 0.00232
+        };
+
+        let v = Self::mat_mul(&AGX_OUTSET, Color3::new(encode(v.x), encode(v.y), encode(v.z)));
+
+        // The contrast curve produces display-encoded values, so undo the display's 2.2 gamma.
+        Color3::new(
+            v.x.max(0.).powf(2.2),
+            v.y.max(0.).powf(2.2),
+            v.z.max(0.).powf(2.2),
+        )
+    }
+
+    fn mat_mul(m: &[[f64; 3]; 3], v: Color3) -> Color3 {
+        Color3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATORS: [ToneMap; 6] = [
+        ToneMap::Clamp,
+        ToneMap::Reinhard,
+        ToneMap::ExtendedReinhard { white: 4. },
+        ToneMap::ExtendedReinhard { white: 0. },
+        ToneMap::Aces,
+        ToneMap::AgX,
+    ];
+
+    #[test]
+    fn operators_are_monotonic_in_grey() {
+        for op in OPERATORS {
+            let mut previous = op.apply(Color3::zero());
+            for i in 1..=400 {
+                let l = 0.05 * i as f64;
+                let mapped = op.apply(Color3::new(l, l, l));
+                for c in 0..3 {
+                    assert!(mapped[c] >= previous[c] - 1e-9, "{op:?} decreases at {l}");
+                }
+                previous = mapped;
+            }
+        }
+    }
+
+    #[test]
+    fn operators_stay_in_display_range() {
+        for op in OPERATORS {
+            for color in [Color3::new(1e6, 0., 0.), Color3::new(0., 0.5, 2.), Color3::new(-1., 3., 0.)] {
+                let mapped = op.apply(color);
+                for c in 0..3 {
+                    assert!((0. ..=1.).contains(&mapped[c]), "{op:?} maps {color:?} to {mapped:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extended_reinhard_maps_white_to_one() {
+        let mapped = ToneMap::ExtendedReinhard { white: 4. }.apply(Color3::new(4., 4., 4.));
+        assert!((mapped.x - 1.).abs() < 1e-9);
+    }
+}
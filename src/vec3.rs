@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-use std::ops::{Add, Div, Index, IndexMut, Mul, Sub};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
 
 /// A 3D vector with `x`, `y`, and `z` components.
 ///
@@ -46,6 +46,19 @@ impl Sub for Vec3 {
     }
 }
 
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    /// Negates every component of the vector.
+    fn neg(self) -> Vec3 {
+        Vec3 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
 impl Mul<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -241,6 +254,11 @@ impl Vec3 {
         }
     }
 
+    /// Returns the vector of the absolute values of the components.
+    pub fn abs(self) -> Self {
+        Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
     /// Normalizes the vector to have a length of 1.
     pub fn normalize(self) -> Self {
         self / self.length()
@@ -259,20 +277,27 @@ impl Vec3 {
         Ok(())
     }
 
-    /// Converts a linear color component to gamma-corrected space.
-    fn linear_to_gamma(linear_component: f64) -> f64 {
-        if linear_component > 0.0 {
-            linear_component.sqrt()
-        } else {
+    /// Computes the relative luminance of a linear sRGB color (Rec. 709 weights).
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
+    /// Converts a linear color component to the sRGB transfer curve.
+    fn linear_to_srgb(linear_component: f64) -> f64 {
+        if linear_component <= 0.0 {
             0.0
+        } else if linear_component <= 0.0031308 {
+            12.92 * linear_component
+        } else {
+            1.055 * linear_component.powf(1.0 / 2.4) - 0.055
         }
     }
 
-    /// Writes the vector as a color to a file, applying gamma correction.
+    /// Writes the vector as a color to a file, applying the sRGB transfer function.
     pub fn write_color<W: Write>(&self, file: &mut W) -> Result<(), Error> {
-        let r = Self::linear_to_gamma(self.x);
-        let g = Self::linear_to_gamma(self.y);
-        let b = Self::linear_to_gamma(self.z);
+        let r = Self::linear_to_srgb(self.x);
+        let g = Self::linear_to_srgb(self.y);
+        let b = Self::linear_to_srgb(self.z);
 
         let intenstity = Interval::new(0.0, 0.999);
         let ir = (256.0 * intenstity.clamp(r)) as i32;
@@ -283,7 +308,7 @@ impl Vec3 {
         Ok(())
     }
 
-    /// Writes the vector as a color to a file with a newline, applying gamma correction.
+    /// Writes the vector as a color to a file with a newline, applying the sRGB transfer function.
     pub fn writeln_color<W: Write>(&self, file: &mut W) -> Result<(), Error> {
         self.write_color(file)?;
         file.write_all(b"\n")?;